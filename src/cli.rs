@@ -0,0 +1,188 @@
+use clap::builder::PossibleValuesParser;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use rustin::{DISTROS, MODULES, THEMES};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+#[command(name = "rustin", version, about = "Fetch implemented in rust")]
+pub struct Cli {
+    /// Read the configuration from this file instead of the default location
+    #[arg(short, long, value_name = "PATH", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Ignore the configuration file and use the defaults
+    #[arg(long, conflicts_with = "config", global = true)]
+    pub no_config: bool,
+
+    /// Read the ASCII art logo from this file
+    #[arg(long, value_name = "PATH", conflicts_with = "no_logo")]
+    pub logo: Option<PathBuf>,
+
+    /// Do not show a logo
+    #[arg(long)]
+    pub no_logo: bool,
+
+    /// Show this built-in distribution logo instead of the detected one
+    #[arg(
+        long,
+        value_name = "NAME",
+        value_parser = distro_parser(),
+        conflicts_with_all = ["logo", "no_logo"]
+    )]
+    pub distro: Option<String>,
+
+    /// Size of the built-in distribution logo
+    #[arg(long, value_enum, value_name = "SIZE")]
+    pub logo_size: Option<LogoSize>,
+
+    /// Draw this PNG or JPEG as the logo on terminals with graphics support
+    #[arg(long, value_name = "PATH", conflicts_with = "no_logo")]
+    pub image: Option<PathBuf>,
+
+    /// Graphics protocol for --image
+    #[arg(long, value_enum, value_name = "PROTOCOL")]
+    pub image_protocol: Option<ImageProtocol>,
+
+    /// Color theme
+    #[arg(long, value_name = "NAME", value_parser = theme_parser())]
+    pub theme: Option<String>,
+
+    /// When to use colors; `auto` honours NO_COLOR and CLICOLOR_FORCE and
+    /// otherwise colors only when stdout is a terminal
+    #[arg(
+        long,
+        value_enum,
+        value_name = "WHEN",
+        default_value_t = ColorWhen::Auto,
+        global = true
+    )]
+    pub color: ColorWhen,
+
+    /// Characters to draw with; `auto` uses ASCII unless the locale is UTF-8
+    #[arg(long, value_enum, value_name = "CHARSET", default_value_t = Charset::Auto)]
+    pub charset: Charset,
+
+    /// Comma-separated list of modules to collect, in display order
+    #[arg(
+        short,
+        long,
+        value_name = "MODULES",
+        value_delimiter = ',',
+        value_parser = module_parser()
+    )]
+    pub modules: Option<Vec<String>>,
+
+    /// Time budget for collecting all modules, in milliseconds
+    #[arg(short, long, value_name = "MS")]
+    pub timeout: Option<u64>,
+
+    /// Report failing or timed-out probes on stderr
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+
+    /// Mask hostname, username, SSID, addresses, serials and home paths
+    #[arg(long, global = true)]
+    pub redact: bool,
+
+    /// With --format markdown, wrap the tables in a collapsed <details> block
+    #[arg(long, requires = "format")]
+    pub details: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    /// Parses the arguments, exiting with a usage error on combinations
+    /// clap cannot express.
+    pub fn parse_checked() -> Cli {
+        let cli = Cli::parse();
+        if cli.details && cli.format != Format::Markdown {
+            Cli::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    "--details can only be used with --format markdown",
+                )
+                .exit();
+        }
+        cli
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Boxed, colored layout for the terminal
+    Text,
+    /// Machine-readable JSON document (see README for the schema)
+    Json,
+    /// Prometheus metrics for a textfile collector
+    Prometheus,
+    /// Markdown tables for bug reports
+    Markdown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorWhen {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Charset {
+    Auto,
+    Unicode,
+    Ascii,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogoSize {
+    Small,
+    Large,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImageProtocol {
+    /// Detect from the terminal, showing the ASCII logo when unsupported
+    Auto,
+    Kitty,
+    Sixel,
+    Iterm2,
+    /// Colored half-block characters, for any terminal with 256 colors
+    Halfblock,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// List the available modules and their field keys
+    Modules,
+    /// Check the configuration file and print where it was loaded from
+    Config,
+    /// Serve the report over HTTP at /json, /metrics and /text
+    Serve {
+        /// Address to listen on
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:9123")]
+        listen: SocketAddr,
+        /// Seconds a collected report is reused before collecting again
+        #[arg(long, value_name = "SECONDS", default_value_t = 10)]
+        refresh: u64,
+    },
+}
+
+fn module_parser() -> PossibleValuesParser {
+    PossibleValuesParser::new(MODULES.iter().map(|module| module.name))
+}
+
+fn distro_parser() -> PossibleValuesParser {
+    PossibleValuesParser::new(DISTROS.iter().map(|distro| distro.name))
+}
+
+fn theme_parser() -> PossibleValuesParser {
+    PossibleValuesParser::new(THEMES.iter().map(|theme| theme.name))
+}
@@ -0,0 +1,304 @@
+use std::fmt;
+use std::io::{self, Read};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Time an external probe may take before it is killed.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(1500);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// How long the pipes may take to close once their holders were killed.
+const KILL_GRACE: Duration = Duration::from_millis(100);
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// Reports failed probes on stderr when enabled.
+pub(crate) fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+pub(crate) fn is_verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+#[derive(Debug)]
+pub enum CommandError {
+    NotFound {
+        program: String,
+    },
+    Io {
+        program: String,
+        source: io::Error,
+    },
+    Timeout {
+        program: String,
+        timeout: Duration,
+    },
+    Failed {
+        program: String,
+        status: ExitStatus,
+        stderr: String,
+    },
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::NotFound { program } => write!(f, "{}: command not found", program),
+            CommandError::Io { program, source } => write!(f, "{}: {}", program, source),
+            CommandError::Timeout { program, timeout } => {
+                write!(f, "{}: timed out after {}ms", program, timeout.as_millis())
+            }
+            CommandError::Failed {
+                program,
+                status,
+                stderr,
+            } => {
+                write!(f, "{}: {}", program, status)?;
+                if let Some(line) = stderr.lines().find(|line| !line.trim().is_empty()) {
+                    write!(f, ": {}", line.trim())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+/// Runs `program` with the default timeout and returns its trimmed stdout.
+pub fn run(program: &str, args: &[&str]) -> Result<String, CommandError> {
+    run_with_timeout(program, args, DEFAULT_TIMEOUT)
+}
+
+/// Runs `script` with `sh -c`.
+pub fn shell_with_timeout(script: &str, timeout: Duration) -> Result<String, CommandError> {
+    report(execute(script, "sh", &["-c", script], timeout))
+}
+
+/// Runs `program`, killing it (and anything it spawned) once `timeout` has
+/// passed. A non-zero exit status is reported as [`CommandError::Failed`].
+pub fn run_with_timeout(
+    program: &str,
+    args: &[&str],
+    timeout: Duration,
+) -> Result<String, CommandError> {
+    report(execute(program, program, args, timeout))
+}
+
+fn report(result: Result<String, CommandError>) -> Result<String, CommandError> {
+    if let Err(err) = &result
+        && is_verbose()
+    {
+        eprintln!("rustin: {}", err);
+    }
+    result
+}
+
+/// Spawns `program`; `name` is what errors call it.
+fn execute(
+    name: &str,
+    program: &str,
+    args: &[&str],
+    timeout: Duration,
+) -> Result<String, CommandError> {
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(unix)]
+    {
+        // A process group of its own lets a timeout take down the whole pipeline.
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    let mut child = command.spawn().map_err(|source| match source.kind() {
+        io::ErrorKind::NotFound => CommandError::NotFound {
+            program: name.to_string(),
+        },
+        _ => CommandError::Io {
+            program: name.to_string(),
+            source,
+        },
+    })?;
+
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                kill(&mut child);
+                return Err(CommandError::Timeout {
+                    program: name.to_string(),
+                    timeout,
+                });
+            }
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(source) => {
+                kill(&mut child);
+                return Err(CommandError::Io {
+                    program: name.to_string(),
+                    source,
+                });
+            }
+        }
+    };
+
+    // A background process the probe left behind may still hold the pipes;
+    // it gets what is left of the deadline before its group is killed.
+    if !(stdout.wait(deadline) && stderr.wait(deadline)) {
+        kill_group(child.id());
+        let grace = Instant::now() + KILL_GRACE;
+        stdout.wait(grace);
+        stderr.wait(grace);
+    }
+    let stdout = stdout.take();
+    let stderr = stderr.take();
+
+    if status.success() {
+        Ok(stdout.trim().to_string())
+    } else {
+        Err(CommandError::Failed {
+            program: name.to_string(),
+            status,
+            stderr,
+        })
+    }
+}
+
+/// A pipe drained by a thread of its own, so that whatever was read so far
+/// can be taken even while something still holds the other end open.
+struct Pipe {
+    buffer: Arc<Mutex<Vec<u8>>>,
+    /// Disconnects once the reader reached the end of the pipe.
+    finished: mpsc::Receiver<()>,
+}
+
+impl Pipe {
+    /// Waits until the pipe is closed or `deadline` has passed, and tells
+    /// whether it was closed.
+    fn wait(&self, deadline: Instant) -> bool {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        !matches!(
+            self.finished.recv_timeout(timeout),
+            Err(RecvTimeoutError::Timeout)
+        )
+    }
+
+    fn take(&self) -> String {
+        let buffer = self.buffer.lock().unwrap_or_else(|err| err.into_inner());
+        String::from_utf8_lossy(&buffer).into_owned()
+    }
+}
+
+fn read_in_background<R: Read + Send + 'static>(pipe: Option<R>) -> Pipe {
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    let (finished, receiver) = mpsc::channel::<()>();
+    let shared = Arc::clone(&buffer);
+    thread::spawn(move || {
+        let _finished = finished;
+        let Some(mut pipe) = pipe else {
+            return;
+        };
+        let mut chunk = [0; 4096];
+        loop {
+            match pipe.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => shared
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .extend_from_slice(&chunk[..read]),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(_) => break,
+            }
+        }
+    });
+    Pipe {
+        buffer,
+        finished: receiver,
+    }
+}
+
+fn kill(child: &mut Child) {
+    kill_group(child.id());
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Kills the process group the child leads, taking along anything it
+/// spawned. The group outlives the child as long as one of its members
+/// runs, so its id cannot have been reused.
+fn kill_group(id: u32) {
+    #[cfg(unix)]
+    // SAFETY: killpg only sends a signal and touches no memory of ours; the
+    // group is the one `process_group(0)` created for the child.
+    unsafe {
+        libc::killpg(id as libc::pid_t, libc::SIGKILL);
+    }
+    #[cfg(not(unix))]
+    let _ = id;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returns_trimmed_stdout() {
+        let stdout = run_with_timeout("echo", &["  1.2.3  "], DEFAULT_TIMEOUT).unwrap();
+        assert_eq!(stdout, "1.2.3");
+    }
+
+    #[test]
+    fn slow_commands_time_out() {
+        let started = Instant::now();
+        let result = run_with_timeout("sleep", &["5"], Duration::from_millis(100));
+        assert!(
+            matches!(result, Err(CommandError::Timeout { .. })),
+            "{:?}",
+            result
+        );
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn non_zero_exit_is_a_failure() {
+        let result = shell_with_timeout("echo broken >&2; exit 3", DEFAULT_TIMEOUT);
+        match result {
+            Err(CommandError::Failed { status, stderr, .. }) => {
+                assert_eq!(status.code(), Some(3));
+                assert_eq!(stderr.trim(), "broken");
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn missing_commands_are_not_found() {
+        let result = run_with_timeout("rustin-no-such-command", &[], DEFAULT_TIMEOUT);
+        assert!(
+            matches!(&result, Err(CommandError::NotFound { program }) if program == "rustin-no-such-command"),
+            "{:?}",
+            result
+        );
+    }
+
+    #[test]
+    fn background_process_holding_stdout_is_killed() {
+        let started = Instant::now();
+        let result = shell_with_timeout("sleep 20 & echo 1.2.3", Duration::from_millis(300));
+        assert_eq!(result.unwrap(), "1.2.3");
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
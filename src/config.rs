@@ -0,0 +1,415 @@
+use crate::graphics::{ColorDepth, GraphicsProtocol};
+use crate::info::{ByteUnit, InfoLine};
+use crate::logo::{LogoSize, find_distro};
+use crate::modules::{MODULES, find_module, is_known_field};
+use crate::output::{Bars, Border, Layout, Palette};
+use crate::redact::Redact;
+use crate::style::Color;
+use crate::theme::{Theme, find_theme};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// User configuration, read from `$XDG_CONFIG_HOME/rustin/config.toml`.
+///
+/// ```toml
+/// modules = ["system", "hardware"]
+/// order = ["system.user", "system.host"]
+/// timeout = 2000
+///
+/// [module_timeouts]
+/// hardware = 500
+///
+/// [logo]
+/// distro = "arch"
+/// size = "small"
+/// image = "/home/me/logo.png"
+/// image_width = 24
+/// halfblocks = "always"
+/// image_colors = "256"
+///
+/// [layout]
+/// position = "left"
+/// gap = 2
+/// align = "center"
+/// separator = ":"
+/// overflow = "truncate"
+///
+/// [border]
+/// style = "rounded"
+/// title = "{system.user}@{system.host}"
+///
+/// [theme]
+/// name = "nord"
+/// label = "#ebcb8b"
+///
+/// [palette]
+/// enabled = true
+/// block = "●"
+/// width = 2
+/// rows = "normal"
+///
+/// [bars]
+/// width = 12
+/// warning = 70
+///
+/// [redact]
+/// enabled = true
+/// ip = false
+///
+/// [fields."hardware.memory"]
+/// label = "RAM"
+/// unit = "mib"
+/// bar = "beside"
+///
+/// [fields."display.cursor"]
+/// enabled = false
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct Config {
+    /// Modules to collect, in display order.
+    pub modules: Vec<String>,
+    /// Fields listed here are shown first, in this order; the rest follow.
+    pub order: Vec<String>,
+    /// Per-field settings, keyed by field key (`hardware.memory`).
+    pub fields: HashMap<String, FieldConfig>,
+    /// Time budget for the whole collection, in milliseconds.
+    pub timeout: u64,
+    /// Per-module time budgets in milliseconds, capped by `timeout`.
+    pub module_timeouts: HashMap<String, u64>,
+    pub logo: LogoConfig,
+    pub layout: Layout,
+    pub border: Border,
+    pub theme: ThemeConfig,
+    pub palette: Palette,
+    pub bars: Bars,
+    pub redact: Redact,
+    /// Report failing or timed-out probes on stderr.
+    pub verbose: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct LogoConfig {
+    /// ASCII art file shown instead of a built-in logo.
+    pub path: Option<PathBuf>,
+    /// Built-in logo to show; detected from os-release when unset.
+    pub distro: Option<String>,
+    pub size: LogoSize,
+    /// PNG or JPEG drawn with the terminal's graphics protocol.
+    pub image: Option<PathBuf>,
+    /// Protocol for `image`; detected from the terminal when unset.
+    pub protocol: Option<GraphicsProtocol>,
+    /// Width of `image` in terminal cells.
+    pub image_width: usize,
+    /// When to draw `image` as colored half blocks instead.
+    pub halfblocks: Halfblocks,
+    /// Colors for half blocks; detected from `COLORTERM` when unset.
+    pub image_colors: Option<ColorDepth>,
+}
+
+/// A bundled theme with some of its colors replaced.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct ThemeConfig {
+    /// One of [`THEMES`](crate::THEMES); `default` when unset.
+    pub name: Option<String>,
+    pub label: Option<Color>,
+    pub value: Option<Color>,
+    pub separator: Option<Color>,
+    pub border: Option<Color>,
+    pub accent: Option<Color>,
+}
+
+impl ThemeConfig {
+    /// The named theme with the colors set here. Unknown names give the
+    /// default theme; [`Config::load`] rejects them.
+    pub fn theme(&self) -> Theme {
+        let mut theme = self
+            .name
+            .as_deref()
+            .and_then(find_theme)
+            .unwrap_or_default();
+        let parts = [
+            (&mut theme.label, self.label),
+            (&mut theme.value, self.value),
+            (&mut theme.separator, self.separator),
+            (&mut theme.border, self.border),
+            (&mut theme.accent, self.accent),
+        ];
+        for (style, color) in parts {
+            if color.is_some() {
+                style.fg = color;
+            }
+        }
+        theme
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Halfblocks {
+    /// When the terminal has no graphics protocol.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct FieldConfig {
+    pub enabled: bool,
+    pub label: Option<String>,
+    /// Unit for byte values; picked from the size when unset.
+    pub unit: Option<ByteUnit>,
+    /// Gauge for percentages and usage values, see [`Bars`](crate::Bars).
+    pub bar: BarMode,
+}
+
+impl Default for FieldConfig {
+    fn default() -> Self {
+        FieldConfig {
+            enabled: true,
+            label: None,
+            unit: None,
+            bar: BarMode::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum BarMode {
+    #[default]
+    Off,
+    /// Before the value.
+    Beside,
+    /// In place of the value, followed by the percentage.
+    Instead,
+}
+
+impl Default for LogoConfig {
+    fn default() -> Self {
+        LogoConfig {
+            path: None,
+            distro: None,
+            size: LogoSize::default(),
+            image: None,
+            protocol: None,
+            image_width: 30,
+            halfblocks: Halfblocks::default(),
+            image_colors: None,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            modules: MODULES
+                .iter()
+                .map(|module| module.name.to_string())
+                .collect(),
+            order: Vec::new(),
+            fields: HashMap::new(),
+            timeout: 3000,
+            module_timeouts: HashMap::new(),
+            logo: LogoConfig::default(),
+            layout: Layout::default(),
+            border: Border::default(),
+            theme: ThemeConfig::default(),
+            palette: Palette::default(),
+            bars: Bars::default(),
+            redact: Redact::default(),
+            verbose: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ConfigError {
+    Io { path: PathBuf, source: io::Error },
+    Parse { path: PathBuf, message: String },
+    Invalid { path: PathBuf, message: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, source } => {
+                write!(f, "could not read {}: {}", path.display(), source)
+            }
+            ConfigError::Parse { path, message } => {
+                write!(f, "invalid config {}: {}", path.display(), message)
+            }
+            ConfigError::Invalid { path, message } => {
+                write!(f, "invalid config {}: {}", path.display(), message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Directory holding rustin's configuration files.
+pub(crate) fn config_dir() -> Option<PathBuf> {
+    if let Ok(xdg_config_home) = env::var("XDG_CONFIG_HOME")
+        && !xdg_config_home.is_empty()
+    {
+        return Some(PathBuf::from(xdg_config_home).join("rustin"));
+    }
+
+    env::var("HOME")
+        .ok()
+        .map(|home_dir| PathBuf::from(home_dir).join(".config").join("rustin"))
+}
+
+/// Directory for files rustin can recreate, such as converted images.
+pub(crate) fn cache_dir() -> Option<PathBuf> {
+    if let Ok(xdg_cache_home) = env::var("XDG_CACHE_HOME")
+        && !xdg_cache_home.is_empty()
+    {
+        return Some(PathBuf::from(xdg_cache_home).join("rustin"));
+    }
+
+    env::var("HOME")
+        .ok()
+        .map(|home_dir| PathBuf::from(home_dir).join(".cache").join("rustin"))
+}
+
+fn default_config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("config.toml"))
+}
+
+impl Config {
+    /// Where [`Config::load`] looks without an explicit path.
+    pub fn default_path() -> Option<PathBuf> {
+        default_config_path()
+    }
+
+    /// Loads the configuration from `path`, or from the default location when
+    /// `path` is `None`. A missing default file yields the default config; a
+    /// missing explicit file is an error.
+    pub fn load(path: Option<&Path>) -> Result<Config, ConfigError> {
+        let (path, explicit) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match default_config_path() {
+                Some(path) => (path, false),
+                None => return Ok(Config::default()),
+            },
+        };
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound && !explicit => {
+                return Ok(Config::default());
+            }
+            Err(source) => return Err(ConfigError::Io { path, source }),
+        };
+
+        Config::parse(&path, &contents)
+    }
+
+    fn parse(path: &Path, contents: &str) -> Result<Config, ConfigError> {
+        let config: Config = toml::from_str(contents).map_err(|err| ConfigError::Parse {
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        })?;
+        config.validate().map_err(|message| ConfigError::Invalid {
+            path: path.to_path_buf(),
+            message,
+        })?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for name in self.modules.iter().chain(self.module_timeouts.keys()) {
+            if find_module(name).is_none() {
+                return Err(format!("unknown module `{}`", name));
+            }
+        }
+        for key in self.order.iter().chain(self.fields.keys()) {
+            if !is_known_field(key) {
+                return Err(format!("unknown field `{}`", key));
+            }
+        }
+        if let Some(distro) = &self.logo.distro
+            && find_distro(distro).is_none()
+        {
+            return Err(format!("unknown distro logo `{}`", distro));
+        }
+        if let Some(theme) = &self.theme.name
+            && find_theme(theme).is_none()
+        {
+            return Err(format!("unknown theme `{}`", theme));
+        }
+        Ok(())
+    }
+
+    pub fn module_timeout(&self, name: &str) -> Duration {
+        let timeout = self
+            .module_timeouts
+            .get(name)
+            .map_or(self.timeout, |timeout| (*timeout).min(self.timeout));
+        Duration::from_millis(timeout)
+    }
+
+    /// Whether `key` will be collected: its module is selected and the field
+    /// is not disabled.
+    pub fn is_enabled(&self, key: &str) -> bool {
+        let module = key.split('.').next().unwrap_or(key);
+        self.modules.iter().any(|name| name == module) && self.field(key).enabled
+    }
+
+    pub fn field(&self, key: &str) -> FieldConfig {
+        self.fields.get(key).cloned().unwrap_or_default()
+    }
+
+    /// The theme from `[theme]`, with the border color still accepted from
+    /// `[border]` for older configurations.
+    pub fn theme(&self) -> Theme {
+        let mut theme = self.theme.theme();
+        if self.theme.border.is_none() && self.border.color.is_some() {
+            theme.border.fg = self.border.color;
+        }
+        theme
+    }
+
+    /// Drops disabled fields, applies label overrides and sorts the lines
+    /// according to `order`.
+    pub fn apply(&self, info: Vec<InfoLine>) -> Vec<InfoLine> {
+        let mut info: Vec<InfoLine> = info
+            .into_iter()
+            .filter(|line| self.field(&line.key).enabled)
+            .map(|mut line| {
+                if let Some(label) = &self.field(&line.key).label {
+                    line.label = label.clone();
+                }
+                line
+            })
+            .collect();
+
+        // Stable sort keeps repeated fields such as disks in collection order.
+        info.sort_by_key(|line| {
+            self.order
+                .iter()
+                .position(|key| *key == line.key)
+                .unwrap_or(self.order.len())
+        });
+        info
+    }
+}
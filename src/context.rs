@@ -0,0 +1,53 @@
+use crate::config::Config;
+use crate::modules::MODULES;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use sysinfo::{CpuRefreshKind, MemoryRefreshKind, RefreshKind, System};
+
+/// State shared by all modules during one collection.
+///
+/// The sysinfo snapshot is taken once and only refreshes what the enabled
+/// fields read, instead of every module scanning the whole system.
+pub struct Context {
+    pub system: System,
+    /// When the collection started; module deadlines count from here.
+    started: Instant,
+    timeouts: HashMap<&'static str, Duration>,
+}
+
+impl Context {
+    pub fn new(config: &Config) -> Self {
+        let started = Instant::now();
+        let mut refresh = RefreshKind::nothing();
+
+        if config.is_enabled("hardware.cpu") {
+            refresh = refresh.with_cpu(CpuRefreshKind::nothing());
+        }
+
+        let mut memory = MemoryRefreshKind::nothing();
+        if config.is_enabled("hardware.memory") {
+            memory = memory.with_ram();
+        }
+        if config.is_enabled("display.swap") {
+            memory = memory.with_swap();
+        }
+        if memory.ram() || memory.swap() {
+            refresh = refresh.with_memory(memory);
+        }
+
+        Context {
+            system: System::new_with_specifics(refresh),
+            started,
+            timeouts: MODULES
+                .iter()
+                .map(|module| (module.name, config.module_timeout(module.name)))
+                .collect(),
+        }
+    }
+
+    /// Time left before `module` is reported as timed out.
+    pub fn remaining(&self, module: &str) -> Duration {
+        let timeout = self.timeouts.get(module).copied().unwrap_or_default();
+        timeout.saturating_sub(self.started.elapsed())
+    }
+}
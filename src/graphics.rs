@@ -0,0 +1,479 @@
+use crate::config::cache_dir;
+use crate::logo::{Logo, Span};
+use crate::style::Color;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat, RgbaImage};
+use serde::Deserialize;
+use std::env;
+use std::fmt;
+use std::fmt::Write;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Half-block conversions kept in the cache; the least recently used ones
+/// beyond this are removed.
+const MAX_CACHED_HALFBLOCKS: usize = 16;
+
+/// Largest payload kitty accepts in one escape sequence.
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// Escape sequences a terminal can use to draw pictures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum GraphicsProtocol {
+    Kitty,
+    Sixel,
+    Iterm2,
+}
+
+/// Guesses the graphics protocol of the terminal from its environment.
+///
+/// Returns `None` inside tmux and screen, which do not pass the sequences
+/// through, and for terminals not known to draw images.
+pub(crate) fn detect_protocol() -> Option<GraphicsProtocol> {
+    let var = |name| env::var(name).unwrap_or_default();
+    let term = var("TERM");
+    let term_program = var("TERM_PROGRAM");
+
+    if env::var_os("TMUX").is_some() || term.starts_with("screen") || term.starts_with("tmux") {
+        return None;
+    }
+    if term == "xterm-kitty"
+        || term == "xterm-ghostty"
+        || env::var_os("KITTY_WINDOW_ID").is_some()
+        || term_program == "ghostty"
+    {
+        return Some(GraphicsProtocol::Kitty);
+    }
+    if term_program == "iTerm.app" || term_program == "WezTerm" || var("LC_TERMINAL") == "iTerm2" {
+        return Some(GraphicsProtocol::Iterm2);
+    }
+    if term.starts_with("foot") || term.starts_with("mlterm") || term.contains("sixel") {
+        return Some(GraphicsProtocol::Sixel);
+    }
+    None
+}
+
+/// Colors available for pictures converted to text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[non_exhaustive]
+pub enum ColorDepth {
+    /// 24-bit colors.
+    #[serde(rename = "truecolor")]
+    TrueColor,
+    /// The 256-color palette.
+    #[serde(rename = "256")]
+    Ansi256,
+}
+
+/// 24-bit colors when `COLORTERM` says so, 256 colors otherwise.
+pub(crate) fn detect_color_depth() -> ColorDepth {
+    match env::var("COLORTERM").as_deref() {
+        Ok("truecolor" | "24bit") => ColorDepth::TrueColor,
+        _ => ColorDepth::Ansi256,
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ImageLogoError {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl fmt::Display for ImageLogoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "could not load image {}: {}",
+            self.path.display(),
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for ImageLogoError {}
+
+/// A picture encoded for one graphics protocol, together with the block of
+/// terminal cells it covers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageLogo {
+    pub columns: usize,
+    pub rows: usize,
+    /// Draws the image with its top left corner at the cursor.
+    sequence: String,
+}
+
+impl ImageLogo {
+    /// Loads a PNG or JPEG and scales it to `columns` cells wide, or fewer
+    /// when it would be taller than `max_rows`. `cell_size` is the width and
+    /// height of one terminal cell in pixels.
+    pub fn load(
+        path: &Path,
+        protocol: GraphicsProtocol,
+        columns: usize,
+        max_rows: usize,
+        cell_size: (u32, u32),
+    ) -> Result<ImageLogo, ImageLogoError> {
+        let error = |message: String| ImageLogoError {
+            path: path.to_path_buf(),
+            message,
+        };
+        let image = image::open(path).map_err(|err| error(err.to_string()))?;
+        if image.width() == 0 || image.height() == 0 {
+            return Err(error("image is empty".to_string()));
+        }
+
+        let (columns, rows) = fit_cells(
+            (image.width(), image.height()),
+            columns,
+            max_rows,
+            cell_size,
+        );
+        let (cell_width, cell_height) = cell_size;
+        let image = image.resize(
+            columns as u32 * cell_width,
+            rows as u32 * cell_height,
+            FilterType::Triangle,
+        );
+
+        let sequence = match protocol {
+            GraphicsProtocol::Kitty => kitty(&png(&image).map_err(error)?),
+            GraphicsProtocol::Iterm2 => iterm2(&png(&image).map_err(error)?, columns, rows),
+            GraphicsProtocol::Sixel => sixel(&image.to_rgba8()),
+        };
+
+        Ok(ImageLogo {
+            columns,
+            rows,
+            sequence,
+        })
+    }
+
+    /// Draws the image `up` lines above the cursor at the 1-based `column`,
+    /// then puts the cursor back where it was.
+    pub(crate) fn place(&self, up: usize, column: usize) -> String {
+        format!("\x1b7\x1b[{}A\x1b[{}G{}\x1b8", up, column, self.sequence)
+    }
+}
+
+/// Converts a PNG or JPEG to text: every cell shows two pixels stacked with
+/// the upper half block, foreground above and background below. The picture
+/// is scaled to fit `columns` by `rows` cells.
+///
+/// Conversions are cached under `$XDG_CACHE_HOME/rustin`, keyed by the
+/// image's contents and the requested size, since decoding and scaling take
+/// longer than the rest of the run. Only the most recently used
+/// [`MAX_CACHED_HALFBLOCKS`] are kept.
+pub(crate) fn halfblock_logo(
+    path: &Path,
+    columns: usize,
+    rows: usize,
+    depth: ColorDepth,
+) -> Result<Logo, ImageLogoError> {
+    let error = |message: String| ImageLogoError {
+        path: path.to_path_buf(),
+        message,
+    };
+    let bytes = fs::read(path).map_err(|err| error(err.to_string()))?;
+    let cache_path = cache_dir().map(|dir| {
+        dir.join(format!(
+            "halfblock-{:016x}.json",
+            halfblock_key(&bytes, columns, rows, depth)
+        ))
+    });
+    if let Some(cache_path) = &cache_path
+        && let Some(logo) = fs::read_to_string(cache_path)
+            .ok()
+            .and_then(|cached| serde_json::from_str(&cached).ok())
+    {
+        // Marks the entry as recently used for pruning
+        let _ = fs::File::options()
+            .append(true)
+            .open(cache_path)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+        return Ok(logo);
+    }
+
+    let image = image::load_from_memory(&bytes).map_err(|err| error(err.to_string()))?;
+    let logo = halfblocks(&image, columns, rows, depth);
+
+    // A cache that cannot be written only costs time on the next run
+    if let Some(cache_path) = cache_path
+        && let Some(dir) = cache_path.parent()
+        && fs::create_dir_all(dir).is_ok()
+        && let Ok(json) = serde_json::to_string(&logo)
+        && fs::write(&cache_path, json).is_ok()
+    {
+        prune_halfblock_cache(dir);
+    }
+
+    Ok(logo)
+}
+
+/// 64-bit FNV-1a of the image and the conversion settings. Written out
+/// rather than taken from `std`, whose hashers may change between releases
+/// and leave the cached files behind.
+fn halfblock_key(bytes: &[u8], columns: usize, rows: usize, depth: ColorDepth) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    let depth: u8 = match depth {
+        ColorDepth::TrueColor => 0,
+        ColorDepth::Ansi256 => 1,
+    };
+    let settings = [
+        (columns as u64).to_le_bytes().as_slice(),
+        (rows as u64).to_le_bytes().as_slice(),
+        &[depth],
+    ]
+    .concat();
+    bytes
+        .iter()
+        .chain(&settings)
+        .fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+        })
+}
+
+/// Removes all but the [`MAX_CACHED_HALFBLOCKS`] most recently used
+/// conversions.
+fn prune_halfblock_cache(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut cached: Vec<(SystemTime, PathBuf)> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("halfblock-") && name.ends_with(".json")
+        })
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .collect();
+    if cached.len() <= MAX_CACHED_HALFBLOCKS {
+        return;
+    }
+    cached.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    for (_, path) in cached.drain(MAX_CACHED_HALFBLOCKS..) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+fn halfblocks(image: &DynamicImage, columns: usize, rows: usize, depth: ColorDepth) -> Logo {
+    if columns == 0 || rows == 0 || image.width() == 0 || image.height() == 0 {
+        return Logo::default();
+    }
+
+    // A cell is about twice as tall as wide, so each half is roughly square
+    let image = image
+        .resize(columns as u32, rows as u32 * 2, FilterType::Triangle)
+        .to_rgba8();
+    let (width, height) = image.dimensions();
+    let color = |x, y| {
+        if y >= height {
+            return None;
+        }
+        let [r, g, b, a] = image.get_pixel(x, y).0;
+        (a >= 128).then(|| match depth {
+            ColorDepth::TrueColor => Color::Rgb(r, g, b),
+            ColorDepth::Ansi256 => Color::Ansi(ansi256(r, g, b)),
+        })
+    };
+
+    let lines = (0..height)
+        .step_by(2)
+        .map(|y| {
+            let mut spans: Vec<Span> = Vec::new();
+            for x in 0..width {
+                let (text, color, background) = match (color(x, y), color(x, y + 1)) {
+                    (Some(top), bottom) => ("▀", Some(top), bottom),
+                    (None, Some(bottom)) => ("▄", Some(bottom), None),
+                    (None, None) => (" ", None, None),
+                };
+                match spans.last_mut() {
+                    Some(span) if span.color == color && span.background == background => {
+                        span.text.push_str(text)
+                    }
+                    _ => spans.push(Span {
+                        text: text.to_string(),
+                        color,
+                        background,
+                    }),
+                }
+            }
+            spans
+        })
+        .collect();
+
+    Logo { lines }
+}
+
+/// Nearest entry of the 256-color palette, from its color cube or gray ramp.
+fn ansi256(r: u8, g: u8, b: u8) -> u8 {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let nearest_level = |value: u8| {
+        (0..6)
+            .min_by_key(|i| (LEVELS[*i] as i32 - value as i32).abs())
+            .unwrap_or(0)
+    };
+    let distance = |(r2, g2, b2): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(r, r2) + d(g, g2) + d(b, b2)
+    };
+
+    let (ri, gi, bi) = (nearest_level(r), nearest_level(g), nearest_level(b));
+    let cube = (LEVELS[ri], LEVELS[gi], LEVELS[bi]);
+
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray_index = ((average.saturating_sub(8)) / 10).min(23) as u8;
+    let gray_level = 8 + gray_index * 10;
+
+    if distance((gray_level, gray_level, gray_level)) < distance(cube) {
+        232 + gray_index
+    } else {
+        16 + (ri * 36 + gi * 6 + bi) as u8
+    }
+}
+
+/// Cells covered by an image of `size` pixels drawn `columns` cells wide,
+/// shrunk to fit `max_rows`.
+fn fit_cells(
+    size: (u32, u32),
+    columns: usize,
+    max_rows: usize,
+    cell_size: (u32, u32),
+) -> (usize, usize) {
+    let (width, height) = (size.0 as f64, size.1 as f64);
+    let (cell_width, cell_height) = (cell_size.0.max(1) as f64, cell_size.1.max(1) as f64);
+
+    let columns = columns.max(1);
+    let rows = (columns as f64 * cell_width * height / width / cell_height).ceil() as usize;
+    if rows <= max_rows || max_rows == 0 {
+        return (columns, rows.max(1));
+    }
+
+    let columns = (max_rows as f64 * cell_height * width / height / cell_width).floor() as usize;
+    (columns.max(1), max_rows)
+}
+
+fn png(image: &DynamicImage) -> Result<Vec<u8>, String> {
+    let mut data = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut data), ImageFormat::Png)
+        .map_err(|err| err.to_string())?;
+    Ok(data)
+}
+
+/// Transmits and displays a PNG in one go, without moving the cursor.
+fn kitty(png: &[u8]) -> String {
+    let payload = BASE64.encode(png);
+    let chunks: Vec<&[u8]> = payload.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+    let mut out = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        let chunk = std::str::from_utf8(chunk).unwrap_or_default();
+        if i == 0 {
+            let _ = write!(out, "\x1b_Ga=T,f=100,q=2,C=1,m={};{}\x1b\\", more, chunk);
+        } else {
+            let _ = write!(out, "\x1b_Gm={};{}\x1b\\", more, chunk);
+        }
+    }
+    out
+}
+
+fn iterm2(png: &[u8], columns: usize, rows: usize) -> String {
+    format!(
+        "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=1:{}\x07",
+        png.len(),
+        columns,
+        rows,
+        BASE64.encode(png)
+    )
+}
+
+/// Encodes the image as sixels using the 6x6x6 color cube. Mostly
+/// transparent pixels are left undrawn.
+fn sixel(image: &RgbaImage) -> String {
+    let (width, height) = image.dimensions();
+    let level = |value: u8| (value as u32 * 5 + 127) / 255;
+    let index = |pixel: &image::Rgba<u8>| {
+        let [r, g, b, a] = pixel.0;
+        (a >= 128).then(|| (level(r) * 36 + level(g) * 6 + level(b)) as usize)
+    };
+
+    let mut out = format!("\x1bP0;1;0q\"1;1;{};{}", width, height);
+    for color in 0..216u32 {
+        let percent = |component: u32| component * 100 / 5;
+        let _ = write!(
+            out,
+            "#{};2;{};{};{}",
+            color,
+            percent(color / 36),
+            percent(color / 6 % 6),
+            percent(color % 6)
+        );
+    }
+
+    for band in (0..height).step_by(6) {
+        let band_height = (height - band).min(6);
+        let mut used = [false; 216];
+        for y in band..band + band_height {
+            for x in 0..width {
+                if let Some(color) = index(image.get_pixel(x, y)) {
+                    used[color] = true;
+                }
+            }
+        }
+
+        let mut first = true;
+        for color in (0..216).filter(|color| used[*color]) {
+            if !first {
+                out.push('$');
+            }
+            first = false;
+            let _ = write!(out, "#{}", color);
+
+            let mut run: Option<(u8, usize)> = None;
+            for x in 0..width {
+                let mut bits = 0u8;
+                for row in 0..band_height {
+                    if index(image.get_pixel(x, band + row)) == Some(color) {
+                        bits |= 1 << row;
+                    }
+                }
+                run = match run {
+                    Some((previous, count)) if previous == bits => Some((bits, count + 1)),
+                    Some((previous, count)) => {
+                        push_sixels(&mut out, previous, count);
+                        Some((bits, 1))
+                    }
+                    None => Some((bits, 1)),
+                };
+            }
+            if let Some((bits, count)) = run {
+                push_sixels(&mut out, bits, count);
+            }
+        }
+        out.push('-');
+    }
+
+    out.push_str("\x1b\\");
+    out
+}
+
+/// Appends `count` copies of the sixel for `bits`, run-length encoded.
+fn push_sixels(out: &mut String, bits: u8, count: usize) {
+    let sixel = (b'?' + bits) as char;
+    if count > 3 {
+        let _ = write!(out, "!{}{}", count, sixel);
+    } else {
+        for _ in 0..count {
+            out.push(sixel);
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use serde::Deserialize;
+use std::fmt;
+
+/// A single piece of collected information.
+///
+/// `key` is a stable, dotted identifier (`hardware.memory`) that does not
+/// change when the label is renamed, `label` is what the user sees and
+/// `value` keeps the raw data so it can be formatted late.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct InfoLine {
+    pub key: String,
+    pub label: String,
+    pub value: Value,
+    /// Short qualifier shown after the value, e.g. the mount point and
+    /// filesystem of a disk.
+    pub note: Option<String>,
+    /// Tells apart lines sharing a key, e.g. the mount point of a disk. Not
+    /// displayed in the text format.
+    pub id: Option<String>,
+    /// Whether the battery is charging, on `hardware.battery`.
+    pub charging: Option<bool>,
+}
+
+impl InfoLine {
+    pub fn new(key: &str, label: &str, value: Value) -> Self {
+        InfoLine {
+            key: key.to_string(),
+            label: label.to_string(),
+            value,
+            note: None,
+            id: None,
+            charging: None,
+        }
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    pub fn with_charging(mut self, charging: bool) -> Self {
+        self.charging = Some(charging);
+        self
+    }
+
+    /// Name of the module that produced this line (`hardware` for `hardware.memory`).
+    pub fn module(&self) -> &str {
+        self.key.split('.').next().unwrap_or(&self.key)
+    }
+
+    pub fn display_value(&self, unit: Option<ByteUnit>) -> String {
+        let mut value = self.value.format(unit);
+        if let Some(note) = self.note.as_deref().filter(|note| !note.is_empty()) {
+            value = format!("{} - {}", value, note);
+        }
+        match self.charging {
+            Some(true) => value + " (Char:Yes)",
+            Some(false) => value + " (Char:No)",
+            None => value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Value {
+    Text(String),
+    /// A program name with its version, if it could be detected.
+    Version {
+        name: String,
+        version: Option<String>,
+    },
+    /// Per-source counts, e.g. installed packages per package manager.
+    Counts(Vec<(String, u64)>),
+    Percentage(f64),
+    /// Used and total amount, in bytes.
+    Usage {
+        used: u64,
+        total: u64,
+    },
+    /// Duration in seconds.
+    Duration(u64),
+    /// Placeholder for a field whose module missed its deadline, with the
+    /// kind of value the field has when collected.
+    TimedOut(Kind),
+}
+
+/// The shape of a [`Value`], as named by the `type` of a JSON field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Kind {
+    Text,
+    Version,
+    Counts,
+    Percentage,
+    Usage,
+    Duration,
+}
+
+impl Kind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Text => "text",
+            Kind::Version => "version",
+            Kind::Counts => "counts",
+            Kind::Percentage => "percentage",
+            Kind::Usage => "usage",
+            Kind::Duration => "duration",
+        }
+    }
+}
+
+impl Value {
+    pub fn text(s: impl Into<String>) -> Self {
+        Value::Text(s.into())
+    }
+
+    pub fn unknown() -> Self {
+        Value::Text("Unknown".to_string())
+    }
+
+    pub fn kind(&self) -> Kind {
+        match self {
+            Value::Text(_) => Kind::Text,
+            Value::Version { .. } => Kind::Version,
+            Value::Counts(_) => Kind::Counts,
+            Value::Percentage(_) => Kind::Percentage,
+            Value::Usage { .. } => Kind::Usage,
+            Value::Duration(_) => Kind::Duration,
+            Value::TimedOut(kind) => *kind,
+        }
+    }
+
+    /// Percentage of `used` over `total` for usage values.
+    pub fn percentage(&self) -> Option<f64> {
+        match self {
+            Value::Percentage(p) => Some(*p),
+            Value::Usage { used, total } if *total > 0 => {
+                Some(*used as f64 / *total as f64 * 100.0)
+            }
+            Value::Usage { .. } => Some(0.0),
+            _ => None,
+        }
+    }
+
+    /// Formats the value, using `unit` for byte amounts instead of picking
+    /// one from the size.
+    pub fn format(&self, unit: Option<ByteUnit>) -> String {
+        match (self, unit) {
+            (Value::Usage { used, total }, Some(unit)) => format_usage(*used, *total, unit),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Text(s) => write!(f, "{}", s),
+            Value::Version { name, version } => match version {
+                Some(version) => write!(f, "{} {}", name, version),
+                None => write!(f, "{}", name),
+            },
+            Value::Counts(counts) => {
+                let parts: Vec<String> = counts
+                    .iter()
+                    .map(|(source, count)| format!("{} ({})", count, source))
+                    .collect();
+                write!(f, "{}", parts.join(", "))
+            }
+            Value::Percentage(p) => write!(f, "{:.0}%", p),
+            Value::Usage { used, total } => write!(
+                f,
+                "{}",
+                format_usage(*used, *total, ByteUnit::for_size(*total))
+            ),
+            Value::Duration(seconds) => write!(f, "{}", format_duration(*seconds)),
+            Value::TimedOut(_) => write!(f, "Timed out"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum ByteUnit {
+    KiB,
+    MiB,
+    GiB,
+    TiB,
+}
+
+impl ByteUnit {
+    /// Largest unit in which `bytes` is at least one.
+    pub fn for_size(bytes: u64) -> Self {
+        const KIB: u64 = 1024;
+        if bytes >= KIB.pow(4) {
+            ByteUnit::TiB
+        } else if bytes >= KIB.pow(3) {
+            ByteUnit::GiB
+        } else if bytes >= KIB.pow(2) {
+            ByteUnit::MiB
+        } else {
+            ByteUnit::KiB
+        }
+    }
+
+    pub fn divisor(self) -> f64 {
+        match self {
+            ByteUnit::KiB => 1024.0,
+            ByteUnit::MiB => 1024.0 * 1024.0,
+            ByteUnit::GiB => 1024.0 * 1024.0 * 1024.0,
+            ByteUnit::TiB => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        }
+    }
+
+    pub fn suffix(self) -> &'static str {
+        match self {
+            ByteUnit::KiB => "K",
+            ByteUnit::MiB => "M",
+            ByteUnit::GiB => "G",
+            ByteUnit::TiB => "T",
+        }
+    }
+
+    pub fn format(self, bytes: u64) -> String {
+        format!("{:.1}{}", bytes as f64 / self.divisor(), self.suffix())
+    }
+}
+
+fn format_usage(used: u64, total: u64, unit: ByteUnit) -> String {
+    let percentage = Value::Usage { used, total }.percentage().unwrap_or(0.0);
+    format!(
+        "{} / {} ({:.0}%)",
+        unit.format(used),
+        unit.format(total),
+        percentage
+    )
+}
+
+pub fn format_duration(seconds: u64) -> String {
+    let hours = seconds / 3600;
+    let minutes = (seconds % 3600) / 60;
+
+    format!("{} hours, {} mins", hours, minutes)
+}
@@ -0,0 +1,55 @@
+//! System information fetch.
+//!
+//! ```no_run
+//! let config = rustin::Config::load(None).unwrap_or_default();
+//! let report = rustin::collect(&config);
+//! let options = rustin::RenderOptions::from_config(&config).unwrap_or_default();
+//! print!("{}", rustin::render(&report, &options));
+//! ```
+
+mod command;
+mod config;
+mod context;
+mod graphics;
+mod info;
+mod logo;
+mod modules;
+mod output;
+mod redact;
+mod report;
+mod style;
+mod theme;
+mod utils;
+
+pub use config::{BarMode, Config, ConfigError, FieldConfig, Halfblocks, LogoConfig, ThemeConfig};
+pub use graphics::{ColorDepth, GraphicsProtocol, ImageLogo, ImageLogoError};
+pub use info::{ByteUnit, InfoLine, Kind, Value};
+pub use logo::{DISTROS, Distro, Logo, LogoError, LogoSize, Span};
+pub use modules::{MODULES, Module};
+pub use output::json::SCHEMA_VERSION;
+pub use output::{
+    Align, Bars, Border, BorderStyle, Charset, Format, Layout, LogoPosition, Overflow, Palette,
+    PaletteRows, RenderOptions,
+};
+pub use redact::Redact;
+pub use report::Report;
+pub use style::{Color, ColorChoice, Style};
+pub use theme::{THEMES, Theme};
+pub use utils::{detect_charset, get_terminal_size};
+
+/// Runs the modules selected in `config` and returns what they found, with
+/// personal data masked when `config.redact` is enabled. Failing probes are
+/// reported on stderr when `config.verbose` is set.
+pub fn collect(config: &Config) -> Report {
+    command::set_verbose(config.verbose);
+    let mut report = Report {
+        lines: modules::collect(config),
+    };
+    config.redact.apply(&mut report);
+    report
+}
+
+/// Turns a report into text in the format chosen by `options`.
+pub fn render(report: &Report, options: &RenderOptions) -> String {
+    output::render(report, options)
+}
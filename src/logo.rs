@@ -0,0 +1,345 @@
+use crate::config::{Config, config_dir};
+use crate::output::LogoPosition;
+use crate::style::{Color, Style};
+use crate::utils::{read_os_release, to_ascii};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// The bundled `logo.txt`, shown when the user has not provided one.
+const DEFAULT_LOGO: &str = include_str!("../logo.txt");
+
+/// Colors used for `${c1}`..`${c6}` when the logo has no palette header.
+const DEFAULT_PALETTE: [Color; 6] = [
+    Color::Blue,
+    Color::BrightWhite,
+    Color::Cyan,
+    Color::Green,
+    Color::Red,
+    Color::Yellow,
+];
+
+/// ASCII art split into colored spans.
+///
+/// The source format is plain text with neofetch-style `${c1}`..`${c6}`
+/// placeholders switching the color of everything after them, across line
+/// ends. An optional first line picks the colors behind the placeholders:
+///
+/// ```text
+/// palette: cyan bright_white #ff8800
+/// ${c1}  /\
+/// ${c1} /  \${c3}!
+/// ```
+///
+/// Palette entries are color names, 256-color indexes or hex colors; missing
+/// or invalid entries keep the default palette. Text before the first
+/// placeholder is not colored.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Logo {
+    pub lines: Vec<Vec<Span>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub text: String,
+    pub color: Option<Color>,
+    pub background: Option<Color>,
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct LogoError {
+    pub path: PathBuf,
+    pub source: io::Error,
+}
+
+impl fmt::Display for LogoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "could not read logo {}: {}",
+            self.path.display(),
+            self.source
+        )
+    }
+}
+
+impl std::error::Error for LogoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl Logo {
+    /// The logo `config` asks for: the file in `logo.path`, then the built-in
+    /// `logo.distro`, then the user's `logo.txt`, then the distro detected
+    /// from os-release and finally the bundled default. Empty when the layout
+    /// has no room for a logo.
+    pub fn from_config(config: &Config) -> Result<Logo, LogoError> {
+        if config.layout.position == LogoPosition::None {
+            return Ok(Logo::default());
+        }
+        if let Some(path) = &config.logo.path {
+            return read_logo(path);
+        }
+        // Validated by the config loader.
+        if let Some(distro) = config.logo.distro.as_deref().and_then(find_distro) {
+            return Ok(distro.logo(config.logo.size));
+        }
+        if let Some(path) = user_logo_path()
+            && path.exists()
+        {
+            return read_logo(&path);
+        }
+        Ok(detect_distro()
+            .map(|distro| distro.logo(config.logo.size))
+            .unwrap_or_else(default_logo))
+    }
+
+    pub fn parse(source: &str) -> Logo {
+        let mut palette = DEFAULT_PALETTE;
+        let mut body = source;
+        let first_line = source.lines().next().unwrap_or_default();
+        if let Some(colors) = first_line.trim().strip_prefix("palette:") {
+            for (slot, color) in palette
+                .iter_mut()
+                .zip(colors.split([' ', ',']).filter(|color| !color.is_empty()))
+            {
+                if let Ok(color) = color.parse() {
+                    *slot = color;
+                }
+            }
+            body = source.split_once('\n').map_or("", |(_, rest)| rest);
+        }
+
+        let mut color = None;
+        let lines = body
+            .lines()
+            .map(|line| {
+                let mut spans: Vec<Span> = Vec::new();
+                let mut rest = line;
+                while !rest.is_empty() {
+                    if let Some(index) = placeholder(rest) {
+                        color = Some(palette[index]);
+                        rest = &rest[5..];
+                        continue;
+                    }
+                    // Everything up to the next placeholder, at least one char
+                    let first = rest.chars().next().map_or(1, char::len_utf8);
+                    let end = rest[first..]
+                        .find("${c")
+                        .map_or(rest.len(), |position| position + first);
+                    match spans.last_mut() {
+                        Some(span) if span.color == color => span.text.push_str(&rest[..end]),
+                        _ => spans.push(Span {
+                            text: rest[..end].to_string(),
+                            color,
+                            background: None,
+                        }),
+                    }
+                    rest = &rest[end..];
+                }
+                spans
+            })
+            .collect();
+
+        Logo { lines }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Columns taken by the widest line.
+    pub fn width(&self) -> usize {
+        self.lines
+            .iter()
+            .map(|line| line.iter().map(|span| span.text.width()).sum())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The first `height` lines.
+    pub fn truncate(&self, height: usize) -> Logo {
+        Logo {
+            lines: self.lines.iter().take(height).cloned().collect(),
+        }
+    }
+
+    /// Every line cut down to at most `width` columns.
+    pub fn crop(&self, width: usize) -> Logo {
+        let lines = self
+            .lines
+            .iter()
+            .map(|line| {
+                let mut remaining = width;
+                let mut cropped = Vec::new();
+                for span in line {
+                    let mut text = String::new();
+                    for ch in span.text.chars() {
+                        let ch_width = ch.width().unwrap_or(0);
+                        if ch_width > remaining {
+                            remaining = 0;
+                            break;
+                        }
+                        remaining -= ch_width;
+                        text.push(ch);
+                    }
+                    if !text.is_empty() {
+                        cropped.push(Span {
+                            text,
+                            ..span.clone()
+                        });
+                    }
+                    if remaining == 0 {
+                        break;
+                    }
+                }
+                cropped
+            })
+            .collect();
+        Logo { lines }
+    }
+
+    /// The logo with its non-ASCII characters replaced, see [`to_ascii`].
+    pub fn to_ascii(&self) -> Logo {
+        let lines = self
+            .lines
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .map(|span| Span {
+                        text: to_ascii(&span.text),
+                        ..span.clone()
+                    })
+                    .collect()
+            })
+            .collect();
+        Logo { lines }
+    }
+
+    /// Each line as text, with escape codes for the span colors when `color`
+    /// is set.
+    pub fn render(&self, color: bool) -> Vec<String> {
+        self.lines
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .map(|span| {
+                        let style = Style {
+                            fg: span.color,
+                            bg: span.background,
+                            bold: false,
+                        };
+                        style.paint(&span.text, color)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+fn read_logo(path: &Path) -> Result<Logo, LogoError> {
+    fs::read_to_string(path)
+        .map(|contents| Logo::parse(&contents))
+        .map_err(|source| LogoError {
+            path: path.to_path_buf(),
+            source,
+        })
+}
+
+/// Palette index of the `${cN}` placeholder at the start of `text`.
+fn placeholder(text: &str) -> Option<usize> {
+    let digit = text.strip_prefix("${c")?.as_bytes();
+    match digit {
+        [n @ b'1'..=b'6', b'}', ..] => Some((n - b'1') as usize),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum LogoSize {
+    Small,
+    #[default]
+    Large,
+}
+
+/// A built-in distribution logo.
+pub struct Distro {
+    /// Name used to pick the logo explicitly (`--distro arch`).
+    pub name: &'static str,
+    /// os-release `ID` values that select this logo.
+    pub ids: &'static [&'static str],
+    large: &'static str,
+    small: &'static str,
+}
+
+macro_rules! distro {
+    ($name:literal, [$($id:literal),*]) => {
+        Distro {
+            name: $name,
+            ids: &[$($id),*],
+            large: include_str!(concat!("../logos/", $name, ".txt")),
+            small: include_str!(concat!("../logos/", $name, "_small.txt")),
+        }
+    };
+}
+
+pub const DISTROS: &[Distro] = &[
+    distro!("alpine", ["alpine"]),
+    distro!("arch", ["arch", "archarm", "endeavouros"]),
+    distro!("debian", ["debian", "raspbian"]),
+    distro!("fedora", ["fedora"]),
+    distro!("gentoo", ["gentoo"]),
+    distro!("linuxmint", ["linuxmint"]),
+    distro!("manjaro", ["manjaro", "manjaro-arm"]),
+    distro!("nixos", ["nixos"]),
+    distro!(
+        "opensuse",
+        ["opensuse", "opensuse-leap", "opensuse-tumbleweed", "suse"]
+    ),
+    distro!("ubuntu", ["ubuntu"]),
+    distro!("void", ["void"]),
+];
+
+impl Distro {
+    pub fn logo(&self, size: LogoSize) -> Logo {
+        match size {
+            LogoSize::Small => Logo::parse(self.small),
+            LogoSize::Large => Logo::parse(self.large),
+        }
+    }
+}
+
+pub(crate) fn find_distro(name: &str) -> Option<&'static Distro> {
+    DISTROS.iter().find(|distro| distro.name == name)
+}
+
+/// The logo matching this machine's os-release `ID`, or failing that the
+/// first of its `ID_LIKE` parents we have art for (Pop!_OS shows Ubuntu).
+pub(crate) fn detect_distro() -> Option<&'static Distro> {
+    let os_release = read_os_release();
+    let id = os_release.get("ID").map(String::as_str).unwrap_or_default();
+    let id_like = os_release
+        .get("ID_LIKE")
+        .map(String::as_str)
+        .unwrap_or_default();
+
+    std::iter::once(id)
+        .chain(id_like.split_whitespace())
+        .find_map(|id| DISTROS.iter().find(|distro| distro.ids.contains(&id)))
+}
+
+pub(crate) fn default_logo() -> Logo {
+    Logo::parse(DEFAULT_LOGO)
+}
+
+/// Where a user's own logo is looked up: `logo.txt` next to the config file.
+pub(crate) fn user_logo_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("logo.txt"))
+}
@@ -1,10 +1,10 @@
-use crate::utils::break_long_text;
-use std::process::Command;
-use sysinfo::{Disks, System};
+use crate::command;
+use crate::context::Context;
+use crate::info::{InfoLine, Value};
+use sysinfo::Disks;
 
-pub fn get_hardware_info() -> Vec<(String, String)> {
-    let mut system = System::new_all();
-    system.refresh_all();
+pub fn get_hardware_info(context: &Context) -> Vec<InfoLine> {
+    let system = &context.system;
     let disks = Disks::new_with_refreshed_list();
 
     let mut info = Vec::new();
@@ -14,142 +14,130 @@ pub fn get_hardware_info() -> Vec<(String, String)> {
         .cpus()
         .first()
         .map_or_else(|| "Unknown".to_string(), |cpu| cpu.brand().to_string());
-    let cpu_lines = break_long_text(&cpu_brand, 35);
-    for (i, line) in cpu_lines.iter().enumerate() {
-        let label = if i == 0 {
-            "CPU".to_string()
-        } else {
-            "".to_string()
-        };
-        info.push((label, line.clone()));
-    }
+    info.push(InfoLine::new("hardware.cpu", "CPU", Value::Text(cpu_brand)));
 
     // GPU info
     let gpu = get_gpu_info();
-    let gpu_lines = break_long_text(&gpu, 35);
-    for (i, line) in gpu_lines.iter().enumerate() {
-        let label = if i == 0 {
-            "GPU".to_string()
-        } else {
-            "".to_string()
-        };
-        info.push((label, line.clone()));
-    }
+    info.push(InfoLine::new("hardware.gpu", "GPU", Value::Text(gpu)));
 
     // Memory info
-    let total_memory = system.total_memory() as f64 / (1024.0 * 1024.0 * 1024.0);
-    let used_memory = system.used_memory() as f64 / (1024.0 * 1024.0 * 1024.0);
-    let memory_percentage = (used_memory / total_memory) * 100.0;
-    info.push((
-        "Memory".to_string(),
-        format!(
-            "{:.1}G / {:.1}G ({:.0}%)",
-            used_memory, total_memory, memory_percentage
-        ),
+    info.push(InfoLine::new(
+        "hardware.memory",
+        "Memory",
+        Value::Usage {
+            used: system.used_memory(),
+            total: system.total_memory(),
+        },
     ));
 
     // Disk info
-    for (i, disk) in disks.list().iter().enumerate() {
-        let total_space = disk.total_space() as f64 / (1024.0 * 1024.0 * 1024.0);
-        let available_space = disk.available_space() as f64 / (1024.0 * 1024.0 * 1024.0);
-        let used_space = total_space - available_space;
-        let percentage = (used_space / total_space) * 100.0;
-        let file_system = disk.file_system().to_string_lossy().into_owned();
-
-        let label = if disks.list().len() == 1 {
-            "Disk".to_string()
-        } else {
-            match file_system.as_str() {
-                "ext4" | "ext3" | "ext2" => "Disk".to_string(), // Main disk
-                "vfat" | "fat32" => "Disk".to_string(),         // EFI/boot partition
-                "swap" => "Swap".to_string(),                   // Swap partition
-                _ => format!("Disk {}", i + 1),
-            }
-        };
+    for disk in disks.list() {
+        let total_space = disk.total_space();
+        let used_space = total_space.saturating_sub(disk.available_space());
+        let mount_point = disk.mount_point().to_string_lossy().into_owned();
+        let file_system = disk.file_system().to_string_lossy();
 
-        info.push((
-            label,
-            format!(
-                "{:.1}G / {:.1}G ({:.0}%) - {}",
-                used_space, total_space, percentage, file_system
-            ),
-        ));
+        // The mount point tells the disks apart, also when the label is
+        // overridden in the config
+        info.push(
+            InfoLine::new(
+                "hardware.disk",
+                "Disk",
+                Value::Usage {
+                    used: used_space,
+                    total: total_space,
+                },
+            )
+            .with_note(format!("{} ({})", mount_point, file_system))
+            .with_id(mount_point),
+        );
     }
 
     // Battery info
-    let battery = get_battery_info();
-    let battery_lines = break_long_text(&battery, 35);
-    for (i, line) in battery_lines.iter().enumerate() {
-        let label = if i == 0 {
-            "Battery".to_string()
-        } else {
-            "".to_string()
-        };
-        info.push((label, line.clone()));
-    }
+    let battery = match get_battery_info() {
+        Some(battery) => {
+            let value = battery
+                .percentage
+                .map_or_else(Value::unknown, Value::Percentage);
+            InfoLine::new("hardware.battery", "Battery", value)
+                .with_note(format!("{} {}", battery.native_path, battery.model))
+                .with_charging(battery.charging)
+        }
+        None => InfoLine::new(
+            "hardware.battery",
+            "Battery",
+            Value::text("No battery found"),
+        ),
+    };
+    info.push(battery);
 
     info
 }
 
 fn get_gpu_info() -> String {
-    Command::new("sh")
-        .arg("-c")
-        .arg("lspci | grep -i 'vga\\|3d\\|display' | head -n1 | sed -E 's/.*: (.*) \\(rev.*\\)/\\1/'")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "Unknown".to_string())
+    let Ok(stdout) = command::run("lspci", &[]) else {
+        return "Unknown".to_string();
+    };
+
+    stdout
+        .lines()
+        .find(|line| {
+            let line = line.to_lowercase();
+            line.contains("vga") || line.contains("3d") || line.contains("display")
+        })
+        .map(|line| {
+            // "00:02.0 VGA compatible controller: Intel Corporation UHD Graphics 620 (rev 07)"
+            let device = line.split_once(": ").map_or(line, |(_, device)| device);
+            let device = device.split(" (rev").next().unwrap_or(device);
+            device.trim().to_string()
+        })
+        .unwrap_or_else(|| "Unknown".to_string())
 }
 
-fn get_battery_info() -> String {
-    let output = Command::new("sh")
-        .arg("-c")
-        .arg("upower -i $(upower -e | grep 'BAT')")
-        .output();
+struct Battery {
+    native_path: String,
+    model: String,
+    percentage: Option<f64>,
+    charging: bool,
+}
 
-    if let Ok(output) = output {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut native_path = "Unknown".to_string();
-        let mut model = "Unknown".to_string();
-        let mut percentage = "Unknown".to_string();
-        let mut state = "Unknown".to_string();
+fn get_battery_info() -> Option<Battery> {
+    let devices = command::run("upower", &["-e"]).ok()?;
+    let device = devices.lines().find(|line| line.contains("BAT"))?;
+    let stdout = command::run("upower", &["-i", device]).ok()?;
+    let mut native_path = None;
+    let mut model = "Unknown".to_string();
+    let mut percentage = None;
+    let mut charging = false;
 
-        for line in stdout.lines() {
-            if line.contains("native-path:") {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() > 1 {
-                    native_path = parts[1].trim_end_matches("...").to_string();
-                }
-            } else if line.contains("model:") {
-                let parts: Vec<&str> = line.splitn(2, ':').collect();
-                if parts.len() > 1 {
-                    model = parts[1].trim().to_string();
-                }
-            } else if line.contains("percentage:") {
-                let parts: Vec<&str> = line.splitn(2, ':').collect();
-                if parts.len() > 1 {
-                    percentage = parts[1].trim().to_string();
-                }
-            } else if line.contains("state:") {
-                let parts: Vec<&str> = line.splitn(2, ':').collect();
-                if parts.len() > 1 {
-                    state = if parts[1].trim() == "charging" {
-                        "Yes".to_string()
-                    } else {
-                        "No".to_string()
-                    };
-                }
+    for line in stdout.lines() {
+        if line.contains("native-path:") {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() > 1 {
+                native_path = Some(parts[1].trim_end_matches("...").to_string());
+            }
+        } else if line.contains("model:") {
+            let parts: Vec<&str> = line.splitn(2, ':').collect();
+            if parts.len() > 1 {
+                model = parts[1].trim().to_string();
+            }
+        } else if line.contains("percentage:") {
+            let parts: Vec<&str> = line.splitn(2, ':').collect();
+            if parts.len() > 1 {
+                percentage = parts[1].trim().trim_end_matches('%').parse::<f64>().ok();
+            }
+        } else if line.contains("state:") {
+            let parts: Vec<&str> = line.splitn(2, ':').collect();
+            if parts.len() > 1 {
+                charging = parts[1].trim() == "charging";
             }
         }
-
-        if native_path != "Unknown" {
-            format!(
-                "{} {} - {} (Char:{})",
-                native_path, model, percentage, state
-            )
-        } else {
-            "No battery found".to_string()
-        }
-    } else {
-        "No battery found".to_string()
     }
+
+    Some(Battery {
+        native_path: native_path?,
+        model,
+        percentage,
+        charging,
+    })
 }
@@ -4,3 +4,134 @@ pub mod network;
 pub mod software;
 pub mod system;
 pub mod terminal;
+
+use crate::config::Config;
+use crate::context::Context;
+use crate::info::{InfoLine, Kind, Value};
+use crate::utils::run_with_deadlines;
+use std::sync::Arc;
+
+/// A collector together with the fields it can produce, as
+/// `(key, label, kind)`.
+pub struct Module {
+    pub name: &'static str,
+    pub fields: &'static [(&'static str, &'static str, Kind)],
+    pub(crate) collect: fn(&Context) -> Vec<InfoLine>,
+}
+
+/// All modules, in their default display order.
+pub const MODULES: &[Module] = &[
+    Module {
+        name: "system",
+        fields: &[
+            ("system.host", "Host", Kind::Text),
+            ("system.user", "User", Kind::Text),
+            ("system.os", "OS", Kind::Version),
+            ("system.kernel", "Kernel", Kind::Version),
+            ("system.uptime", "Uptime", Kind::Duration),
+        ],
+        collect: system::get_system_info,
+    },
+    Module {
+        name: "software",
+        fields: &[
+            ("software.shell", "Shell", Kind::Version),
+            ("software.packages", "Packages", Kind::Counts),
+        ],
+        collect: software::get_software_info,
+    },
+    Module {
+        name: "network",
+        fields: &[("network.connection", "Network", Kind::Text)],
+        collect: network::get_network_info,
+    },
+    Module {
+        name: "terminal",
+        fields: &[
+            ("terminal.name", "Terminal", Kind::Text),
+            ("terminal.font", "Font", Kind::Text),
+        ],
+        collect: terminal::get_terminal_info,
+    },
+    Module {
+        name: "display",
+        fields: &[
+            ("display.wm", "WM/DE", Kind::Text),
+            ("display.swap", "Swap", Kind::Usage),
+            ("display.arch", "Arch", Kind::Text),
+            ("display.cursor", "Cursor", Kind::Text),
+            ("display.resolution", "Resolution", Kind::Text),
+        ],
+        collect: display::get_display_info,
+    },
+    Module {
+        name: "hardware",
+        fields: &[
+            ("hardware.cpu", "CPU", Kind::Text),
+            ("hardware.gpu", "GPU", Kind::Text),
+            ("hardware.memory", "Memory", Kind::Usage),
+            ("hardware.disk", "Disk", Kind::Usage),
+            ("hardware.battery", "Battery", Kind::Percentage),
+        ],
+        collect: hardware::get_hardware_info,
+    },
+];
+
+pub fn find_module(name: &str) -> Option<&'static Module> {
+    MODULES.iter().find(|module| module.name == name)
+}
+
+pub fn is_known_field(key: &str) -> bool {
+    field_kind(key).is_some()
+}
+
+/// The kind of value `key` has when collected.
+pub fn field_kind(key: &str) -> Option<Kind> {
+    MODULES
+        .iter()
+        .flat_map(|module| module.fields)
+        .find(|(field, _, _)| *field == key)
+        .map(|(_, _, kind)| *kind)
+}
+
+/// Runs the configured modules concurrently and returns their lines in
+/// configuration order.
+///
+/// Each module gets its own deadline (its entry in `module_timeouts`, capped
+/// by the global `timeout`). A module that misses it is reported with a
+/// placeholder for each of its fields.
+pub fn collect(config: &Config) -> Vec<InfoLine> {
+    let modules: Vec<&Module> = config
+        .modules
+        .iter()
+        .filter_map(|name| find_module(name))
+        .collect();
+
+    let context = Arc::new(Context::new(config));
+    let tasks = modules
+        .iter()
+        .map(|module| {
+            let context = Arc::clone(&context);
+            let collect = module.collect;
+            let task = move || collect(&context);
+            (config.module_timeout(module.name), task)
+        })
+        .collect();
+
+    let mut info = Vec::new();
+    for (module, lines) in modules.iter().zip(run_with_deadlines(tasks)) {
+        match lines {
+            Some(lines) => info.extend(lines),
+            None => info.extend(timed_out_lines(module)),
+        }
+    }
+    config.apply(info)
+}
+
+fn timed_out_lines(module: &Module) -> Vec<InfoLine> {
+    module
+        .fields
+        .iter()
+        .map(|(key, label, kind)| InfoLine::new(key, label, Value::TimedOut(*kind)))
+        .collect()
+}
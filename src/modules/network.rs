@@ -1,29 +1,39 @@
-use std::process::Command;
+use crate::command;
+use crate::context::Context;
+use crate::info::{InfoLine, Value};
 use sysinfo::Networks;
 
-pub fn get_network_info() -> Vec<(String, String)> {
+pub fn get_network_info(_context: &Context) -> Vec<InfoLine> {
     let networks = Networks::new_with_refreshed_list();
-    let mut info = Vec::new();
 
-    let network_ssid = get_network_ssid();
-    let mut network_info = "No network".to_string();
-    for (interface, data) in &networks {
-        if data.total_received() > 0 || data.total_transmitted() > 0 {
-            network_info = format!("{} ({})", network_ssid, interface.clone());
-            break;
-        }
-    }
+    let active_interface = networks
+        .iter()
+        .find(|(_, data)| data.total_received() > 0 || data.total_transmitted() > 0)
+        .map(|(interface, _)| interface.clone());
 
-    info.push(("Network".to_string(), network_info));
+    let line = match active_interface {
+        Some(interface) => {
+            let network_ssid = get_network_ssid();
+            if network_ssid.is_empty() {
+                InfoLine::new("network.connection", "Network", Value::Text(interface))
+            } else {
+                InfoLine::new("network.connection", "Network", Value::Text(network_ssid))
+                    .with_note(interface)
+            }
+        }
+        None => InfoLine::new("network.connection", "Network", Value::text("No network")),
+    };
 
-    info
+    vec![line]
 }
 
 fn get_network_ssid() -> String {
-    Command::new("sh")
-        .arg("-c")
-        .arg("nmcli -t -f active,ssid dev wifi | awk -F: '$1==\"yes\"{print $2}'")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "Unknown".to_string())
+    command::run("nmcli", &["-t", "-f", "active,ssid", "dev", "wifi"])
+        .ok()
+        .and_then(|stdout| {
+            stdout
+                .lines()
+                .find_map(|line| line.strip_prefix("yes:").map(|ssid| ssid.to_string()))
+        })
+        .unwrap_or_default()
 }
@@ -1,98 +1,55 @@
-use crate::utils::break_long_text;
+use crate::command;
+use crate::context::Context;
+use crate::info::{InfoLine, Value};
+use crate::utils::run_with_deadlines;
 use regex::Regex;
-use std::fs;
-use std::process::Command;
+use std::time::Duration;
 
-pub fn get_software_info() -> Vec<(String, String)> {
-    let mut info = Vec::new();
+/// Time kept back from the module deadline to hand the results over.
+const DEADLINE_MARGIN: Duration = Duration::from_millis(100);
 
-    // Shell info
-    let shell = get_shell_info();
-    info.push(("Shell".to_string(), shell));
+pub fn get_software_info(context: &Context) -> Vec<InfoLine> {
+    let budget = || {
+        context
+            .remaining("software")
+            .saturating_sub(DEADLINE_MARGIN)
+    };
+    let shell = get_shell_info(budget().min(command::DEFAULT_TIMEOUT));
 
-    // Packages info
-    let packages = get_package_count();
-    let packages_lines = break_long_text(&packages, 35);
-    for (i, line) in packages_lines.iter().enumerate() {
-        let label = if i == 0 {
-            "Packages".to_string()
-        } else {
-            "".to_string()
-        };
-        info.push((label, line.clone()));
-    }
+    let packages = get_package_count(budget());
+    let packages = if packages.is_empty() {
+        Value::text("No packages found")
+    } else {
+        Value::Counts(packages)
+    };
 
-    info
+    vec![
+        InfoLine::new("software.shell", "Shell", shell),
+        InfoLine::new("software.packages", "Packages", packages),
+    ]
 }
 
-fn get_shell_info() -> String {
+fn get_shell_info(timeout: Duration) -> Value {
     let shell_path = std::env::var("SHELL").unwrap_or_else(|_| "unknown".to_string());
-    let shell_name = shell_path.split('/').last().unwrap_or("unknown");
-    let version = if shell_name == "bash" {
-        Command::new(shell_name)
-            .arg("--version")
-            .output()
-            .ok()
-            .and_then(|output| {
-                if output.status.success() {
-                    let stdout = String::from_utf8_lossy(&output.stdout);
-                    let re = Regex::new(r"version (\d+\.\d+\.\d+(?:\(\d+\))?-\w+)").ok()?;
-                    re.captures(&stdout).map(|caps| caps[1].to_string())
-                } else {
-                    None
-                }
-            })
-            .unwrap_or_else(|| "unknown".to_string())
-    } else if shell_name == "zsh" {
-        Command::new(shell_name)
-            .arg("--version")
-            .output()
-            .ok()
-            .and_then(|output| {
-                if output.status.success() {
-                    let stdout = String::from_utf8_lossy(&output.stdout);
-                    let re = Regex::new(r"zsh (\d+\.\d+\.\d+)").ok()?;
-                    re.captures(&stdout).map(|caps| caps[1].to_string())
-                } else {
-                    None
-                }
-            })
-            .unwrap_or_else(|| "unknown".to_string())
-    } else if shell_name == "fish" {
-        Command::new(shell_name)
-            .arg("--version")
-            .output()
-            .ok()
-            .and_then(|output| {
-                if output.status.success() {
-                    let stdout = String::from_utf8_lossy(&output.stdout);
-                    let re = Regex::new(r"version (\d+\.\d+\.\d+)").ok()?;
-                    re.captures(&stdout).map(|caps| caps[1].to_string())
-                } else {
-                    None
-                }
-            })
-            .unwrap_or_else(|| "unknown".to_string())
-    } else {
-        // Generic fallback
-        Command::new(shell_name)
-            .arg("--version")
-            .output()
-            .ok()
-            .and_then(|output| {
-                if output.status.success() {
-                    let stdout = String::from_utf8_lossy(&output.stdout);
-                    // Look for version pattern in output
-                    let re = Regex::new(r"(\d+\.\d+\.\d+)").ok()?;
-                    re.captures(&stdout).map(|caps| caps[1].to_string())
-                } else {
-                    None
-                }
-            })
-            .unwrap_or_else(|| "unknown".to_string())
+    let shell_name = shell_path.rsplit('/').next().unwrap_or("unknown");
+    let pattern = match shell_name {
+        "bash" => r"version (\d+\.\d+\.\d+(?:\(\d+\))?-\w+)",
+        "zsh" => r"zsh (\d+\.\d+\.\d+)",
+        "fish" => r"version (\d+\.\d+\.\d+)",
+        // Generic fallback: look for a version pattern in the output
+        _ => r"(\d+\.\d+\.\d+)",
     };
+    let version = command::run_with_timeout(shell_name, &["--version"], timeout)
+        .ok()
+        .and_then(|stdout| {
+            let re = Regex::new(pattern).ok()?;
+            re.captures(&stdout).map(|caps| caps[1].to_string())
+        });
 
-    format!("{} {}", shell_name, version)
+    Value::Version {
+        name: shell_name.to_string(),
+        version,
+    }
 }
 
 // fn get_os_info_from_os_release() -> (String, String) {
@@ -130,244 +87,76 @@ fn get_shell_info() -> String {
 //     ("Unknown".to_string(), "Unknown".to_string())
 // }
 
-fn get_package_count() -> String {
-    let mut package_counts = Vec::new();
-
-    // dpkg (Debian/Ubuntu)
-    let dpkg_count = Command::new("sh")
-        .arg("-c")
-        .arg("dpkg --list | grep '^ii' | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if dpkg_count != "0" {
-        package_counts.push(format!("{} (dpkg)", dpkg_count));
-    }
-
-    // apt (Debian/Ubuntu)
-    let apt_count = Command::new("sh")
-        .arg("-c")
-        .arg("apt list --installed 2>/dev/null | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if apt_count != "0" {
-        package_counts.push(format!("{} (apt)", apt_count));
-    }
-
-    // pacman (Arch Linux)
-    let pacman_count = Command::new("sh")
-        .arg("-c")
-        .arg("pacman -Q 2>/dev/null | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if pacman_count != "0" {
-        package_counts.push(format!("{} (pacman)", pacman_count));
-    }
-
-    // yay (Arch Linux AUR)
-    let yay_count = Command::new("sh")
-        .arg("-c")
-        .arg("yay -Q 2>/dev/null | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if yay_count != "0" {
-        package_counts.push(format!("{} (yay)", yay_count));
-    }
-
-    // paru (Arch Linux AUR)
-    let paru_count = Command::new("sh")
-        .arg("-c")
-        .arg("paru -Q 2>/dev/null | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if paru_count != "0" {
-        package_counts.push(format!("{} (paru)", paru_count));
-    }
-
-    // rpm (Red Hat/Fedora)
-    let rpm_count = Command::new("sh")
-        .arg("-c")
-        .arg("rpm -qa 2>/dev/null | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if rpm_count != "0" {
-        package_counts.push(format!("{} (rpm)", rpm_count));
-    }
-
-    // dnf (Fedora)
-    let dnf_count = Command::new("sh")
-        .arg("-c")
-        .arg("dnf list installed 2>/dev/null | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if dnf_count != "0" {
-        package_counts.push(format!("{} (dnf)", dnf_count));
-    }
-
-    // yum (Red Hat/CentOS)
-    let yum_count = Command::new("sh")
-        .arg("-c")
-        .arg("yum list installed 2>/dev/null | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if yum_count != "0" {
-        package_counts.push(format!("{} (yum)", yum_count));
-    }
-
-    // zypper (openSUSE)
-    let zypper_count = Command::new("sh")
-        .arg("-c")
-        .arg("zypper search --installed-only 2>/dev/null | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if zypper_count != "0" {
-        package_counts.push(format!("{} (zypper)", zypper_count));
-    }
-
-    // emerge (Gentoo)
-    let emerge_count = Command::new("sh")
-        .arg("-c")
-        .arg("qlist -I 2>/dev/null | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if emerge_count != "0" {
-        package_counts.push(format!("{} (emerge)", emerge_count));
-    }
-
-    // nix (NixOS)
-    let nix_count = Command::new("sh")
-        .arg("-c")
-        .arg("nix-store -q --requisites /run/current-system/sw 2>/dev/null | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if nix_count != "0" {
-        package_counts.push(format!("{} (nix)", nix_count));
-    }
-
-    // brew (macOS/Linux)
-    let brew_count = Command::new("sh")
-        .arg("-c")
-        .arg("brew list 2>/dev/null | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if brew_count != "0" {
-        package_counts.push(format!("{} (brew)", brew_count));
-    }
-
-    // flatpak
-    let flatpak_count = Command::new("sh")
-        .arg("-c")
-        .arg("flatpak list --app 2>/dev/null | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if flatpak_count != "0" {
-        package_counts.push(format!("{} (flatpak)", flatpak_count));
-    }
-
-    // snap
-    let snap_count = Command::new("sh")
-        .arg("-c")
-        .arg("snap list 2>/dev/null | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if snap_count != "0" {
-        package_counts.push(format!("{} (snap)", snap_count));
-    }
-
-    // cargo (Rust)
-    let cargo_count = Command::new("sh")
-        .arg("-c")
-        .arg("cargo install --list 2>/dev/null | grep '^[a-zA-Z]' | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if cargo_count != "0" {
-        package_counts.push(format!("{} (cargo)", cargo_count));
-    }
-
-    // pip (Python)
-    let pip_count = Command::new("sh")
-        .arg("-c")
-        .arg("pip list 2>/dev/null | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if pip_count != "0" {
-        package_counts.push(format!("{} (pip)", pip_count));
-    }
-
-    // npm (Node.js)
-    let npm_count = Command::new("sh")
-        .arg("-c")
-        .arg("npm list -g --depth=0 2>/dev/null | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if npm_count != "0" {
-        package_counts.push(format!("{} (npm)", npm_count));
-    }
-
-    // portage (Gentoo)
-    let portage_count = Command::new("sh")
-        .arg("-c")
-        .arg("ls /var/db/pkg/*/*/PF 2>/dev/null | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if portage_count != "0" {
-        package_counts.push(format!("{} (portage)", portage_count));
-    }
-
-    // apk (Alpine Linux)
-    let apk_count = Command::new("sh")
-        .arg("-c")
-        .arg("apk info 2>/dev/null | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if apk_count != "0" {
-        package_counts.push(format!("{} (apk)", apk_count));
-    }
-
-    // xbps (Void Linux)
-    let xbps_count = Command::new("sh")
-        .arg("-c")
-        .arg("xbps-query -l 2>/dev/null | grep '^ii' | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if xbps_count != "0" {
-        package_counts.push(format!("{} (xbps)", xbps_count));
-    }
-
-    // pkg (FreeBSD)
-    let pkg_count = Command::new("sh")
-        .arg("-c")
-        .arg("pkg info 2>/dev/null | wc -l")
-        .output()
-        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
-        .unwrap_or_else(|_| "0".to_string());
-    if pkg_count != "0" {
-        package_counts.push(format!("{} (pkg)", pkg_count));
-    }
-
-    // Return formatted string
-    if package_counts.is_empty() {
-        "No packages found".to_string()
-    } else {
-        package_counts.join(", ")
-    }
+/// Package managers and the shell pipeline that prints their installed package count.
+const PACKAGE_MANAGERS: &[(&str, &str)] = &[
+    // Debian/Ubuntu
+    ("dpkg", "dpkg --list | grep '^ii' | wc -l"),
+    ("apt", "apt list --installed 2>/dev/null | wc -l"),
+    // Arch Linux
+    ("pacman", "pacman -Q 2>/dev/null | wc -l"),
+    ("yay", "yay -Q 2>/dev/null | wc -l"),
+    ("paru", "paru -Q 2>/dev/null | wc -l"),
+    // Red Hat/Fedora
+    ("rpm", "rpm -qa 2>/dev/null | wc -l"),
+    ("dnf", "dnf list installed 2>/dev/null | wc -l"),
+    ("yum", "yum list installed 2>/dev/null | wc -l"),
+    // openSUSE
+    (
+        "zypper",
+        "zypper search --installed-only 2>/dev/null | wc -l",
+    ),
+    // Gentoo
+    ("emerge", "qlist -I 2>/dev/null | wc -l"),
+    // NixOS
+    (
+        "nix",
+        "nix-store -q --requisites /run/current-system/sw 2>/dev/null | wc -l",
+    ),
+    // macOS/Linux
+    ("brew", "brew list 2>/dev/null | wc -l"),
+    ("flatpak", "flatpak list --app 2>/dev/null | wc -l"),
+    ("snap", "snap list 2>/dev/null | wc -l"),
+    // Language package managers
+    (
+        "cargo",
+        "cargo install --list 2>/dev/null | grep '^[a-zA-Z]' | wc -l",
+    ),
+    ("pip", "pip list 2>/dev/null | wc -l"),
+    ("npm", "npm list -g --depth=0 2>/dev/null | wc -l"),
+    // Gentoo
+    ("portage", "ls /var/db/pkg/*/*/PF 2>/dev/null | wc -l"),
+    // Alpine Linux
+    ("apk", "apk info 2>/dev/null | wc -l"),
+    // Void Linux
+    ("xbps", "xbps-query -l 2>/dev/null | grep '^ii' | wc -l"),
+    // FreeBSD
+    ("pkg", "pkg info 2>/dev/null | wc -l"),
+];
+
+/// Counts for the package managers that answer within `timeout`; the others
+/// are left out rather than holding up the module.
+fn get_package_count(timeout: Duration) -> Vec<(String, u64)> {
+    let tasks = PACKAGE_MANAGERS
+        .iter()
+        .map(|(_, script)| {
+            let task = move || {
+                command::shell_with_timeout(script, timeout)
+                    .ok()
+                    .and_then(|stdout| stdout.parse::<u64>().ok())
+                    .unwrap_or(0)
+            };
+            // The command kills itself at the timeout; the extra margin lets
+            // that error be reported instead of the task being abandoned.
+            (timeout + DEADLINE_MARGIN / 2, task)
+        })
+        .collect();
+
+    PACKAGE_MANAGERS
+        .iter()
+        .zip(run_with_deadlines(tasks))
+        .filter_map(|((manager, _), count)| match count {
+            Some(count) if count != 0 => Some((manager.to_string(), count)),
+            _ => None,
+        })
+        .collect()
 }
@@ -1,18 +1,18 @@
+use crate::command;
+use crate::context::Context;
+use crate::info::{InfoLine, Value};
 use regex::Regex;
 use std::env;
 use std::fs;
-use std::process::Command;
-
-pub fn get_terminal_info() -> Vec<(String, String)> {
-    let mut info = Vec::new();
 
+pub fn get_terminal_info(_context: &Context) -> Vec<InfoLine> {
     let terminal = get_terminal_info_internal();
-    info.push(("Terminal".to_string(), terminal));
-
     let font = get_terminal_font();
-    info.push(("Font".to_string(), font));
 
-    info
+    vec![
+        InfoLine::new("terminal.name", "Terminal", Value::Text(terminal)),
+        InfoLine::new("terminal.font", "Font", Value::Text(font)),
+    ]
 }
 
 fn get_terminal_info_internal() -> String {
@@ -39,51 +39,40 @@ fn get_terminal_info_internal() -> String {
         }
     }
 
-    if let Ok(kitty_pid) = env::var("KITTY_PID") {
-        if !kitty_pid.is_empty() {
-            let version = get_terminal_version("kitty").unwrap_or_else(|| "unknown".to_string());
-            return format!("kitty {} ({})", version, term);
-        }
+    if let Ok(kitty_pid) = env::var("KITTY_PID")
+        && !kitty_pid.is_empty()
+    {
+        let version = get_terminal_version("kitty").unwrap_or_else(|| "unknown".to_string());
+        return format!("kitty {} ({})", version, term);
     }
 
-    if let Ok(alacritty_log) = env::var("ALACRITTY_LOG") {
-        if !alacritty_log.is_empty() {
-            let version =
-                get_terminal_version("alacritty").unwrap_or_else(|| "unknown".to_string());
-            return format!("alacritty {} ({})", version, term);
-        }
+    if let Ok(alacritty_log) = env::var("ALACRITTY_LOG")
+        && !alacritty_log.is_empty()
+    {
+        let version = get_terminal_version("alacritty").unwrap_or_else(|| "unknown".to_string());
+        return format!("alacritty {} ({})", version, term);
     }
 
-    if let Ok(gnome_terminal_id) = env::var("GNOME_TERMINAL_SCREEN") {
-        if !gnome_terminal_id.is_empty() {
-            let version =
-                get_terminal_version("gnome-terminal").unwrap_or_else(|| "unknown".to_string());
-            return format!("gnome-terminal {} ({})", version, term);
-        }
+    if let Ok(gnome_terminal_id) = env::var("GNOME_TERMINAL_SCREEN")
+        && !gnome_terminal_id.is_empty()
+    {
+        let version =
+            get_terminal_version("gnome-terminal").unwrap_or_else(|| "unknown".to_string());
+        return format!("gnome-terminal {} ({})", version, term);
     }
 
     term
 }
 
 fn get_terminal_version(terminal: &str) -> Option<String> {
-    let output = Command::new(terminal)
-        .arg("--version")
-        .output()
-        .ok()
-        .and_then(|output| {
-            if output.status.success() {
-                String::from_utf8(output.stdout).ok()
-            } else {
-                None
-            }
-        })?;
+    let output = command::run(terminal, &["--version"]).ok()?;
 
     let version_re = Regex::new(r"\b(\d+\.\d+(?:\.\d+)?)\b").unwrap();
     if let Some(captures) = version_re.captures(&output) {
         return Some(captures[1].to_string());
     }
 
-    let parts: Vec<&str> = output.trim().split_whitespace().collect();
+    let parts: Vec<&str> = output.split_whitespace().collect();
     if parts.len() >= 2 && parts[1].chars().any(|c| c.is_ascii_digit()) {
         return Some(parts[1].to_string());
     }
@@ -193,6 +182,7 @@ fn get_alacritty_font() -> Option<String> {
         format!("{}/.alacritty.toml", home_dir),
         format!("{}/.alacritty.yml", home_dir),
     ];
+    let family_re = Regex::new(r#""([^"]+)""#).ok()?;
 
     for path in &config_paths {
         if let Ok(contents) = fs::read_to_string(path) {
@@ -200,11 +190,10 @@ fn get_alacritty_font() -> Option<String> {
                 // TOML format parsing
                 for line in contents.lines() {
                     let trimmed = line.trim();
-                    if trimmed.starts_with("family =") {
-                        let re = Regex::new(r#""([^"]+)""#).ok()?;
-                        if let Some(caps) = re.captures(trimmed) {
-                            return Some(caps[1].to_string());
-                        }
+                    if trimmed.starts_with("family =")
+                        && let Some(caps) = family_re.captures(trimmed)
+                    {
+                        return Some(caps[1].to_string());
                     }
                 }
             } else {
@@ -264,15 +253,12 @@ fn detect_font_from_config() -> Option<String> {
         }
     }
 
-    if let Ok(output) = Command::new("xrdb").arg("-query").output() {
-        if output.status.success() {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            for line in output_str.lines() {
-                if line.starts_with("*.font:") || line.starts_with("xterm*font:") {
-                    let parts: Vec<&str> = line.splitn(2, ':').collect();
-                    if parts.len() > 1 {
-                        return Some(parts[1].trim().trim_matches('"').to_string());
-                    }
+    if let Ok(output) = command::run("xrdb", &["-query"]) {
+        for line in output.lines() {
+            if line.starts_with("*.font:") || line.starts_with("xterm*font:") {
+                let parts: Vec<&str> = line.splitn(2, ':').collect();
+                if parts.len() > 1 {
+                    return Some(parts[1].trim().trim_matches('"').to_string());
                 }
             }
         }
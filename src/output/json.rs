@@ -0,0 +1,84 @@
+use crate::info::{InfoLine, Value};
+use crate::report::Report;
+use serde_json::{Map, json};
+
+/// Version of the JSON document layout. Bump it whenever a field is renamed,
+/// removed or changes type; adding fields does not require a bump.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Serializes the report, grouped by module in order of appearance.
+pub fn render(report: &Report) -> String {
+    let modules: Vec<serde_json::Value> = report
+        .modules()
+        .into_iter()
+        .map(|(name, lines)| {
+            let fields: Vec<serde_json::Value> = lines.into_iter().map(field_json).collect();
+            json!({ "name": name, "fields": fields })
+        })
+        .collect();
+
+    let document = json!({
+        "schema_version": SCHEMA_VERSION,
+        "modules": modules,
+    });
+    let mut out = serde_json::to_string_pretty(&document).unwrap_or_default();
+    out.push('\n');
+    out
+}
+
+fn field_json(line: &InfoLine) -> serde_json::Value {
+    let mut field = Map::new();
+    field.insert("key".to_string(), json!(line.key));
+    field.insert("label".to_string(), json!(line.label));
+    if let serde_json::Value::Object(value) = value_json(&line.value) {
+        field.extend(value);
+    }
+    if let Some(note) = &line.note {
+        field.insert("note".to_string(), json!(note));
+    }
+    if let Some(id) = &line.id {
+        field.insert("id".to_string(), json!(id));
+    }
+    if let Some(charging) = line.charging {
+        field.insert("charging".to_string(), json!(charging));
+    }
+    serde_json::Value::Object(field)
+}
+
+fn value_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Text(text) => json!({ "type": "text", "value": text }),
+        Value::Version { name, version } => json!({
+            "type": "version",
+            "name": name,
+            "version": version,
+        }),
+        Value::Counts(counts) => {
+            let counts: Vec<serde_json::Value> = counts
+                .iter()
+                .map(|(source, count)| json!({ "source": source, "count": count }))
+                .collect();
+            json!({ "type": "counts", "value": counts })
+        }
+        Value::Percentage(percentage) => json!({
+            "type": "percentage",
+            "value": percentage,
+            "unit": "percent",
+        }),
+        Value::Usage { used, total } => json!({
+            "type": "usage",
+            "used": used,
+            "total": total,
+            "percent": value.percentage(),
+            "unit": "bytes",
+        }),
+        Value::Duration(seconds) => json!({
+            "type": "duration",
+            "value": seconds,
+            "unit": "seconds",
+        }),
+        // Keeps the type of the field, so its shape only differs by the
+        // missing members
+        Value::TimedOut(kind) => json!({ "type": kind.name(), "status": "timed_out" }),
+    }
+}
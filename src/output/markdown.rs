@@ -0,0 +1,54 @@
+use crate::output::RenderOptions;
+use crate::report::Report;
+use std::fmt::Write;
+
+/// A table of the fields per module, wrapped in a collapsed `<details>`
+/// block when `options.details` is set. Plain text only, ready to paste into
+/// an issue.
+pub fn render(report: &Report, options: &RenderOptions) -> String {
+    let mut out = String::new();
+    if options.details {
+        let _ = writeln!(
+            out,
+            "<details>\n<summary>System information (rustin {})</summary>\n",
+            env!("CARGO_PKG_VERSION")
+        );
+    }
+
+    for (i, (module, lines)) in report.modules().into_iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let _ = writeln!(out, "### {}\n", heading(module));
+        let _ = writeln!(out, "| Field | Value |");
+        let _ = writeln!(out, "|-------|-------|");
+        for line in lines {
+            let value = line.display_value(options.field(&line.key).unit);
+            let _ = writeln!(out, "| {} | {} |", escape(&line.label), escape(&value));
+        }
+    }
+
+    if options.details {
+        let _ = writeln!(out, "\n</details>");
+    }
+    out
+}
+
+/// `system` as `System`.
+fn heading(module: &str) -> String {
+    let mut chars = module.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Keeps a value inside its table cell, and shows `<hostname>` and other
+/// text in angle brackets instead of letting it be taken for HTML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('|', "\\|")
+        .replace(['\r', '\n'], " ")
+}
@@ -0,0 +1,344 @@
+pub mod json;
+pub mod markdown;
+pub mod prometheus;
+pub mod text;
+
+pub(crate) use text::art_area;
+
+use crate::config::{Config, FieldConfig, Halfblocks};
+use crate::graphics::{
+    ImageLogo, ImageLogoError, detect_color_depth, detect_protocol, halfblock_logo,
+};
+use crate::logo::{Logo, LogoError};
+use crate::report::Report;
+use crate::style::Color;
+use crate::theme::Theme;
+use crate::utils::get_cell_size;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::IsTerminal;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Format {
+    /// Boxed, colored layout for the terminal.
+    #[default]
+    Text,
+    /// JSON document, see [`json::SCHEMA_VERSION`].
+    Json,
+    /// Prometheus text format, for the node exporter's textfile collector.
+    Prometheus,
+    /// Tables per module, for pasting into issues.
+    Markdown,
+}
+
+/// Where the logo goes relative to the information.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum LogoPosition {
+    Left,
+    /// Beside the information, or above it when the terminal is too narrow.
+    #[default]
+    Right,
+    Top,
+    Bottom,
+    None,
+}
+
+/// Vertical placement of the shorter of logo and information side by side.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Align {
+    #[default]
+    Top,
+    Center,
+}
+
+/// What happens to values wider than the room left for them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Overflow {
+    /// Continue on the next rows, below the start of the value.
+    #[default]
+    Wrap,
+    /// Cut off, ending in `Layout::ellipsis`.
+    Truncate,
+}
+
+/// Arrangement of the logo and the information in the text format.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct Layout {
+    pub position: LogoPosition,
+    /// Columns between logo and information side by side, or blank lines
+    /// between them stacked. When unset, the logo is pushed against the
+    /// border side by side and one line is left stacked.
+    pub gap: Option<usize>,
+    pub align: Align,
+    /// Written right after each label, e.g. `":"`.
+    pub separator: String,
+    pub overflow: Overflow,
+    /// Ends truncated values; may be empty.
+    pub ellipsis: String,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout {
+            position: LogoPosition::default(),
+            gap: None,
+            align: Align::default(),
+            separator: String::new(),
+            overflow: Overflow::default(),
+            ellipsis: "…".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum BorderStyle {
+    /// `┌─┐│└┘`
+    #[default]
+    Single,
+    /// `╭─╮│╰╯`
+    Rounded,
+    /// `╔═╗║╚╝`
+    Double,
+    /// `┏━┓┃┗┛`
+    Heavy,
+    /// `+-+|++`
+    Ascii,
+    /// No box at all.
+    None,
+}
+
+/// The box around the text format.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct Border {
+    pub style: BorderStyle,
+    /// Text set into the top border. `{system.user}` and other field keys in
+    /// braces are replaced by the field's value; fields that were not
+    /// collected are left as they are.
+    pub title: Option<String>,
+    /// Deprecated spelling of `[theme] border`, which wins when both are set.
+    pub color: Option<Color>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum PaletteRows {
+    /// Colors 0-7.
+    Normal,
+    /// Colors 8-15.
+    Bright,
+    #[default]
+    Both,
+}
+
+/// Swatches of the terminal's 16 colors below the information.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct Palette {
+    pub enabled: bool,
+    /// Text drawn in each color.
+    pub block: String,
+    /// Times `block` is repeated per color.
+    pub width: usize,
+    pub rows: PaletteRows,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette {
+            enabled: false,
+            block: "█".to_string(),
+            width: 3,
+            rows: PaletteRows::default(),
+        }
+    }
+}
+
+/// Look of the gauges enabled with `bar` in a field's settings, e.g.
+/// `[████░░░░] 52%`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct Bars {
+    /// Columns between the brackets.
+    pub width: usize,
+    pub filled: String,
+    pub empty: String,
+    /// Percentage from which the bar takes `warning_color`. Battery bars
+    /// count the drained charge, so an almost empty battery is red.
+    pub warning: f64,
+    /// Percentage from which the bar takes `critical_color`.
+    pub critical: f64,
+    pub color: Color,
+    pub warning_color: Color,
+    pub critical_color: Color,
+}
+
+impl Default for Bars {
+    fn default() -> Self {
+        Bars {
+            width: 10,
+            filled: "█".to_string(),
+            empty: "░".to_string(),
+            warning: 60.0,
+            critical: 85.0,
+            color: Color::Green,
+            warning_color: Color::Yellow,
+            critical_color: Color::Red,
+        }
+    }
+}
+
+/// Characters the text format may use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Charset {
+    #[default]
+    Unicode,
+    /// Only ASCII: borders are drawn with `+-|` and anything else outside
+    /// ASCII is replaced, see [`to_ascii`](crate::to_ascii).
+    Ascii,
+}
+
+/// Everything that decides how a [`Report`] is turned into text.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct RenderOptions {
+    pub format: Format,
+    /// Columns available to the text layout.
+    pub width: usize,
+    /// Rows available to the text layout; taller logos are cut down to fit.
+    pub height: usize,
+    /// Emit ANSI color codes. Nothing else is consulted, so the output only
+    /// depends on the options and the report.
+    pub color: bool,
+    /// Art shown next to the information; empty for none.
+    pub logo: Logo,
+    /// Picture drawn instead of `logo` when the whole box fits on the
+    /// screen; `logo` is shown otherwise.
+    pub image: Option<ImageLogo>,
+    /// Per-field display settings, keyed by field key.
+    pub fields: HashMap<String, FieldConfig>,
+    pub layout: Layout,
+    pub border: Border,
+    pub theme: Theme,
+    /// Only drawn with `color`.
+    pub palette: Palette,
+    pub bars: Bars,
+    pub charset: Charset,
+    /// Wrap the Markdown format in a collapsed `<details>` block.
+    pub details: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            format: Format::default(),
+            width: 80,
+            height: 24,
+            color: false,
+            logo: Logo::default(),
+            image: None,
+            fields: HashMap::new(),
+            layout: Layout::default(),
+            border: Border::default(),
+            theme: Theme::default(),
+            palette: Palette::default(),
+            bars: Bars::default(),
+            charset: Charset::default(),
+            details: false,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Options carrying the display settings of `config`, with the logo it
+    /// asks for, see [`Logo::from_config`].
+    pub fn from_config(config: &Config) -> Result<Self, LogoError> {
+        Ok(RenderOptions {
+            logo: Logo::from_config(config)?,
+            fields: config.fields.clone(),
+            layout: config.layout.clone(),
+            border: config.border.clone(),
+            theme: config.theme(),
+            palette: config.palette.clone(),
+            bars: config.bars.clone(),
+            ..RenderOptions::default()
+        })
+    }
+
+    /// Shows the picture in `config.logo.image` instead of the ASCII logo:
+    /// drawn with the terminal's graphics protocol, or converted to half
+    /// blocks sized to the room the layout leaves next to `report`. Without a
+    /// forced protocol, graphics are only drawn when stdout is a terminal
+    /// known to support them; without colors, Unicode or room the ASCII logo
+    /// stays. Set `width`, `height`, `color` and `charset` first.
+    pub fn load_image(&mut self, config: &Config, report: &Report) -> Result<(), ImageLogoError> {
+        let Some(path) = &config.logo.image else {
+            return Ok(());
+        };
+        if self.layout.position == LogoPosition::None {
+            return Ok(());
+        }
+
+        let protocol = match config.logo.protocol {
+            Some(protocol) => Some(protocol),
+            None if std::io::stdout().is_terminal() => detect_protocol(),
+            None => None,
+        };
+        if config.logo.halfblocks != Halfblocks::Always
+            && let Some(protocol) = protocol
+        {
+            self.image = Some(ImageLogo::load(
+                path,
+                protocol,
+                config.logo.image_width,
+                self.height.saturating_sub(4),
+                get_cell_size(),
+            )?);
+            return Ok(());
+        }
+
+        if config.logo.halfblocks == Halfblocks::Never
+            || !self.color
+            || self.charset == Charset::Ascii
+        {
+            return Ok(());
+        }
+        let (columns, rows) = art_area(report, self);
+        let columns = columns.min(config.logo.image_width);
+        if columns == 0 || rows == 0 {
+            return Ok(());
+        }
+        let depth = config.logo.image_colors.unwrap_or_else(detect_color_depth);
+        self.logo = halfblock_logo(path, columns, rows, depth)?;
+        Ok(())
+    }
+
+    pub fn field(&self, key: &str) -> FieldConfig {
+        self.fields.get(key).cloned().unwrap_or_default()
+    }
+}
+
+pub fn render(report: &Report, options: &RenderOptions) -> String {
+    match options.format {
+        Format::Text => text::render(report, options),
+        Format::Json => json::render(report),
+        Format::Prometheus => prometheus::render(report),
+        Format::Markdown => markdown::render(report, options),
+    }
+}
@@ -0,0 +1,192 @@
+use crate::info::{InfoLine, Kind, Value};
+use crate::modules::field_kind;
+use crate::report::Report;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Text fields that change while the system runs, left out of `rustin_info`
+/// so it keeps a single series.
+const VOLATILE_FIELDS: &[&str] = &["network.connection"];
+
+/// A metric with its samples, written together as the format requires.
+struct Family {
+    name: String,
+    help: String,
+    samples: Vec<(Vec<(String, String)>, f64)>,
+}
+
+/// Serializes the report in the Prometheus text format: numeric fields as
+/// `rustin_<key>` gauges and text fields as labels of a single `rustin_info`
+/// metric. Notes are free text and are not exported, so samples only gain a
+/// new series when a disk is mounted or the like. Placeholders such as
+/// "No battery found" in numeric fields are left out, and modules that
+/// missed their deadline are flagged by `rustin_collect_timeout`.
+pub fn render(report: &Report) -> String {
+    let mut families: Vec<Family> = Vec::new();
+    let mut info: Vec<(String, String)> = Vec::new();
+
+    for (index, line) in indexed_lines(report) {
+        let mut labels = Vec::new();
+        match (&line.id, index) {
+            (Some(id), _) => labels.push((id_label(&line.key).to_string(), id.clone())),
+            (None, Some(index)) => labels.push(("index".to_string(), index.to_string())),
+            (None, None) => {}
+        }
+
+        let name = format!("rustin_{}", line.key.replace('.', "_"));
+        let mut add = |suffix: &str, help: String, labels: Vec<(String, String)>, value: f64| {
+            let name = format!("{}{}", name, suffix);
+            match families.iter_mut().find(|family| family.name == name) {
+                Some(family) => family.samples.push((labels, value)),
+                None => families.push(Family {
+                    name,
+                    help,
+                    samples: vec![(labels, value)],
+                }),
+            }
+        };
+        match &line.value {
+            Value::Usage { used, total } => {
+                add(
+                    "_used_bytes",
+                    format!("{} used", line.label),
+                    labels.clone(),
+                    *used as f64,
+                );
+                add(
+                    "_total_bytes",
+                    format!("{} total", line.label),
+                    labels,
+                    *total as f64,
+                );
+            }
+            Value::Percentage(percentage) => {
+                add("_ratio", line.label.clone(), labels, percentage / 100.0);
+            }
+            Value::Duration(seconds) => {
+                add("_seconds", line.label.clone(), labels, *seconds as f64);
+            }
+            Value::Counts(counts) => {
+                for (source, count) in counts {
+                    let mut labels = labels.clone();
+                    labels.push(("source".to_string(), source.clone()));
+                    add("", line.label.clone(), labels, *count as f64);
+                }
+            }
+            Value::TimedOut(_) => {}
+            Value::Text(_) | Value::Version { .. } => {
+                let label = line.key.replace('.', "_");
+                if is_text_field(&line.key)
+                    && !VOLATILE_FIELDS.contains(&line.key.as_str())
+                    && !info.iter().any(|(name, _)| *name == label)
+                {
+                    info.push((label, line.value.to_string()));
+                }
+            }
+        }
+        if let Some(charging) = line.charging {
+            add(
+                "_charging",
+                "Battery charging".to_string(),
+                Vec::new(),
+                if charging { 1.0 } else { 0.0 },
+            );
+        }
+    }
+
+    let timeouts: Vec<(Vec<(String, String)>, f64)> = report
+        .modules()
+        .into_iter()
+        .map(|(module, lines)| {
+            let timed_out = lines
+                .iter()
+                .any(|line| matches!(line.value, Value::TimedOut(_)));
+            let labels = vec![("module".to_string(), module.to_string())];
+            (labels, if timed_out { 1.0 } else { 0.0 })
+        })
+        .collect();
+    if !timeouts.is_empty() {
+        families.push(Family {
+            name: "rustin_collect_timeout".to_string(),
+            help: "Whether the module missed its deadline".to_string(),
+            samples: timeouts,
+        });
+    }
+
+    let mut out = String::new();
+    if !info.is_empty() {
+        families.insert(
+            0,
+            Family {
+                name: "rustin_info".to_string(),
+                help: "Collected text facts".to_string(),
+                samples: vec![(info, 1.0)],
+            },
+        );
+    }
+    for family in families {
+        let _ = writeln!(out, "# HELP {} {}", family.name, escape_help(&family.help));
+        let _ = writeln!(out, "# TYPE {} gauge", family.name);
+        for (labels, value) in family.samples {
+            let _ = writeln!(out, "{}{} {}", family.name, format_labels(&labels), value);
+        }
+    }
+    out
+}
+
+/// Each line with its position among the lines sharing its key, for keys
+/// that appear more than once such as `hardware.disk`.
+fn indexed_lines(report: &Report) -> Vec<(Option<usize>, &InfoLine)> {
+    let mut totals: HashMap<&str, usize> = HashMap::new();
+    for line in &report.lines {
+        *totals.entry(line.key.as_str()).or_default() += 1;
+    }
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    report
+        .lines
+        .iter()
+        .map(|line| {
+            let key = line.key.as_str();
+            let seen = seen.entry(key).or_default();
+            let index = *seen;
+            *seen += 1;
+            ((totals[key] > 1).then_some(index), line)
+        })
+        .collect()
+}
+
+/// Name of the label holding the `id` of a field that occurs more than once.
+fn id_label(key: &str) -> &'static str {
+    match key {
+        "hardware.disk" => "mountpoint",
+        _ => "id",
+    }
+}
+
+/// Whether `key` holds text when collected. Numeric fields also fall back
+/// to text such as "No battery found", which is not a fact worth a label.
+fn is_text_field(key: &str) -> bool {
+    matches!(field_kind(key), Some(Kind::Text | Kind::Version) | None)
+}
+
+fn format_labels(labels: &[(String, String)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let labels: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape_label(value)))
+        .collect();
+    format!("{{{}}}", labels.join(","))
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn escape_help(help: &str) -> String {
+    help.replace('\\', "\\\\").replace('\n', "\\n")
+}
@@ -0,0 +1,719 @@
+use crate::config::BarMode;
+use crate::info::{InfoLine, Value};
+use crate::logo::Logo;
+use crate::output::{
+    Align, BorderStyle, Charset, Layout, LogoPosition, Overflow, PaletteRows, RenderOptions,
+};
+use crate::report::Report;
+use crate::style::{Color, Style};
+use crate::theme::Theme;
+use crate::utils::*;
+use std::fmt::Write;
+
+/// Fields whose bars fill up as the value goes down, so the warning colors
+/// follow the empty part.
+const INVERTED_BARS: &[&str] = &["hardware.battery"];
+
+/// Minimum gap between the information and the art in the side-by-side layout.
+const ART_GAP: usize = 3;
+
+/// Fewest columns values are fitted into beside the art. With less room they
+/// get the whole width of the box, and the art goes above or below them.
+const MIN_VALUE_WIDTH: usize = 30;
+
+const NORMAL_COLORS: [Color; 8] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::White,
+];
+
+const BRIGHT_COLORS: [Color; 8] = [
+    Color::BrightBlack,
+    Color::BrightRed,
+    Color::BrightGreen,
+    Color::BrightYellow,
+    Color::BrightBlue,
+    Color::BrightMagenta,
+    Color::BrightCyan,
+    Color::BrightWhite,
+];
+
+/// Renders the boxed layout into exactly `options.width` columns, or at most
+/// that many without a border. Widths below [`min_width`] are raised to it.
+pub fn render(report: &Report, options: &RenderOptions) -> String {
+    let layout = &options.layout;
+    let frame = Frame::new(report, options);
+    let min_width = min_width(&report.lines, &frame, options);
+    if options.width < min_width {
+        let options = RenderOptions {
+            width: min_width,
+            ..options.clone()
+        };
+        return render(report, &options);
+    }
+    let rows = info_rows(&report.lines, &frame, options);
+    let mut out = String::new();
+    let logo = match options.charset {
+        Charset::Unicode => options.logo.clone(),
+        Charset::Ascii => options.logo.to_ascii(),
+    };
+
+    let info = info_block(&rows, &frame, options);
+    let max_info_width = info.width;
+    let side_by_side = matches!(layout.position, LogoPosition::Left | LogoPosition::Right);
+    let fits_side_by_side =
+        |art_width| side_by_side && art_width <= side_art_width(&frame, max_info_width, options);
+
+    let max_art_height = options.height.saturating_sub(frame.height() + 2);
+
+    // The picture needs the whole box on screen to be placed over it afterwards
+    if let Some(image) = &options.image
+        && layout.position != LogoPosition::None
+        && image.rows <= max_art_height
+    {
+        let reserved = Block {
+            lines: vec![" ".repeat(image.columns); image.rows],
+            width: image.columns,
+        };
+        let fits = if fits_side_by_side(image.columns) {
+            info.lines.len().max(image.rows) + frame.height() <= options.height
+        } else {
+            image.columns <= frame.inner_width(options.width)
+                && info.lines.len() + image.rows + stacked_gap(layout) + frame.height()
+                    <= options.height
+        };
+        if fits {
+            let (row, column) = display(&mut out, &frame, &reserved, &info, layout, options);
+            let up = out.lines().count() - row;
+            out.push_str(&image.place(up, column));
+            return out;
+        }
+    }
+
+    // Format ASCII art for display based on the available height
+    let formatted_art = format_ascii_art_for_display(&logo, max_art_height);
+    let display_logo = if layout.position == LogoPosition::None {
+        Logo::default()
+    } else if fits_side_by_side(formatted_art.width()) {
+        formatted_art
+    } else {
+        // Stacked, possibly as the fallback for a narrow terminal
+        formatted_art.crop(frame.inner_width(options.width))
+    };
+    let art = Block {
+        width: display_logo.width(),
+        lines: display_logo.render(options.color),
+    };
+
+    display(&mut out, &frame, &art, &info, layout, options);
+    out
+}
+
+/// Lays out `art` and `info` side by side when they fit, stacked otherwise.
+/// Returns the line and 1-based column where the art starts.
+fn display(
+    out: &mut String,
+    frame: &Frame,
+    art: &Block,
+    info: &Block,
+    layout: &Layout,
+    options: &RenderOptions,
+) -> (usize, usize) {
+    let side_by_side = !art.lines.is_empty()
+        && matches!(layout.position, LogoPosition::Left | LogoPosition::Right)
+        && art.width <= side_art_width(frame, info.width, options);
+
+    let art_first = matches!(layout.position, LogoPosition::Left | LogoPosition::Top)
+        || (layout.position == LogoPosition::Right && !side_by_side);
+    if side_by_side {
+        display_side_by_side(out, frame, art, info, art_first, layout, options.width)
+    } else {
+        display_stacked(out, frame, art, info, art_first, layout, options.width)
+    }
+}
+
+/// Pre-rendered lines and the columns the widest of them takes.
+struct Block {
+    lines: Vec<String>,
+    width: usize,
+}
+
+/// Columns and rows left for the logo, so pictures can be converted to
+/// exactly the size the layout will show.
+pub(crate) fn art_area(report: &Report, options: &RenderOptions) -> (usize, usize) {
+    let layout = &options.layout;
+    let frame = Frame::new(report, options);
+    let info = info_block(&info_rows(&report.lines, &frame, options), &frame, options);
+    let max_art_height = options.height.saturating_sub(frame.height() + 2);
+    match layout.position {
+        LogoPosition::Left | LogoPosition::Right => {
+            (side_art_width(&frame, info.width, options), max_art_height)
+        }
+        LogoPosition::Top | LogoPosition::Bottom => (
+            frame.inner_width(options.width),
+            max_art_height.saturating_sub(info.lines.len() + stacked_gap(layout)),
+        ),
+        LogoPosition::None => (0, 0),
+    }
+}
+
+/// The `label value` rows, followed by the palette after a blank line.
+fn info_block(rows: &[(String, String)], frame: &Frame, options: &RenderOptions) -> Block {
+    let (max_label_len, max_info_width) = info_widths(rows, &options.layout);
+    let mut lines: Vec<String> = rows
+        .iter()
+        .map(|(label, value)| format!("{} {}", frame.label(label, max_label_len), value))
+        .collect();
+    let mut width = max_info_width;
+
+    let palette = palette_lines(options);
+    if !palette.is_empty() {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        let palette_width = palette.iter().map(|line| visible_width(line)).max();
+        width = width.max(palette_width.unwrap_or(0));
+        lines.extend(palette);
+    }
+    Block { lines, width }
+}
+
+/// A row of swatches for each palette row shown; none without color, where
+/// they would all look the same.
+fn palette_lines(options: &RenderOptions) -> Vec<String> {
+    let palette = &options.palette;
+    if !palette.enabled || !options.color {
+        return Vec::new();
+    }
+    let swatch = charset_text(&palette.block, options).repeat(palette.width);
+    let rows: &[[Color; 8]] = match palette.rows {
+        PaletteRows::Normal => &[NORMAL_COLORS],
+        PaletteRows::Bright => &[BRIGHT_COLORS],
+        PaletteRows::Both => &[NORMAL_COLORS, BRIGHT_COLORS],
+    };
+    rows.iter()
+        .map(|colors| {
+            colors
+                .iter()
+                .map(|color| Style::fg(*color).paint(&swatch, true))
+                .collect()
+        })
+        .collect()
+}
+
+/// Widest label with its separator, and widest `label value` row.
+fn info_widths(info: &[(String, String)], layout: &Layout) -> (usize, usize) {
+    let separator_width = visible_width(&layout.separator);
+    let max_label_len = info
+        .iter()
+        .filter(|(label, _)| !label.is_empty())
+        .map(|(label, _)| visible_width(label) + separator_width)
+        .max()
+        .unwrap_or(0);
+
+    // Calculate info width
+    let mut max_info_width = 0;
+    for (_, value) in info {
+        let line_width = max_label_len + 1 + visible_width(value); // label + " " + value
+        if line_width > max_info_width {
+            max_info_width = line_width;
+        }
+    }
+    (max_label_len, max_info_width)
+}
+
+/// Widest art that fits beside the information:
+/// "│ " + info + gap + art + " │".
+fn side_art_width(frame: &Frame, max_info_width: usize, options: &RenderOptions) -> usize {
+    let gap = options.layout.gap.unwrap_or(ART_GAP);
+    frame
+        .inner_width(options.width)
+        .saturating_sub(max_info_width + gap)
+}
+
+/// Blank lines between art and information when stacked.
+fn stacked_gap(layout: &Layout) -> usize {
+    layout.gap.unwrap_or(1)
+}
+
+/// Corners and edges of a border style.
+struct BorderChars {
+    top_left: char,
+    top_right: char,
+    bottom_left: char,
+    bottom_right: char,
+    horizontal: char,
+    vertical: char,
+}
+
+impl BorderChars {
+    const fn new(corners: [char; 4], horizontal: char, vertical: char) -> Self {
+        let [top_left, top_right, bottom_left, bottom_right] = corners;
+        BorderChars {
+            top_left,
+            top_right,
+            bottom_left,
+            bottom_right,
+            horizontal,
+            vertical,
+        }
+    }
+
+    fn of(style: BorderStyle) -> Option<Self> {
+        Some(match style {
+            BorderStyle::Single => BorderChars::new(['┌', '┐', '└', '┘'], '─', '│'),
+            BorderStyle::Rounded => BorderChars::new(['╭', '╮', '╰', '╯'], '─', '│'),
+            BorderStyle::Double => BorderChars::new(['╔', '╗', '╚', '╝'], '═', '║'),
+            BorderStyle::Heavy => BorderChars::new(['┏', '┓', '┗', '┛'], '━', '┃'),
+            BorderStyle::Ascii => BorderChars::new(['+', '+', '+', '+'], '-', '|'),
+            BorderStyle::None => return None,
+        })
+    }
+}
+
+/// Border pieces and label style, painted once per render.
+struct Frame {
+    color: bool,
+    theme: Theme,
+    separator: String,
+    /// `None` for borderless output.
+    chars: Option<BorderChars>,
+    vertical: String,
+    title: Option<String>,
+}
+
+impl Frame {
+    fn new(report: &Report, options: &RenderOptions) -> Self {
+        let theme = options.theme;
+        let style = match (options.charset, options.border.style) {
+            (Charset::Ascii, style) if style != BorderStyle::None => BorderStyle::Ascii,
+            (_, style) => style,
+        };
+        let chars = BorderChars::of(style);
+        let vertical = chars.as_ref().map_or(String::new(), |chars| {
+            theme
+                .border
+                .paint(&chars.vertical.to_string(), options.color)
+        });
+        Frame {
+            color: options.color,
+            theme,
+            separator: theme.separator.paint(
+                &charset_text(&options.layout.separator, options),
+                options.color,
+            ),
+            chars,
+            vertical,
+            title: options
+                .border
+                .title
+                .as_deref()
+                .map(|title| charset_text(&expand_title(title, report, options), options)),
+        }
+    }
+
+    /// Columns taken on each side of the content: "│ ", or nothing without
+    /// a border.
+    fn side_width(&self) -> usize {
+        if self.chars.is_some() { 2 } else { 0 }
+    }
+
+    /// Lines taken by the top and bottom borders.
+    fn height(&self) -> usize {
+        match (&self.chars, &self.title) {
+            (Some(_), _) => 2,
+            (None, Some(_)) => 1,
+            (None, None) => 0,
+        }
+    }
+
+    /// Lines written before the first content line.
+    fn top_height(&self) -> usize {
+        self.height().min(1)
+    }
+
+    /// Columns left for the content in a box `width` columns wide.
+    fn inner_width(&self, width: usize) -> usize {
+        width.saturating_sub(2 * self.side_width())
+    }
+
+    /// `label` and the separator, padded to `width`. Continuation rows have
+    /// neither.
+    fn label(&self, label: &str, width: usize) -> String {
+        if label.is_empty() {
+            return " ".repeat(width);
+        }
+        let padding = width.saturating_sub(visible_width(label) + visible_width(&self.separator));
+        format!(
+            "{}{}{}",
+            self.theme.label.paint(label, self.color),
+            self.separator,
+            " ".repeat(padding)
+        )
+    }
+
+    /// Writes the top border, with the title set into it after one edge
+    /// piece: "┌─ title ───┐".
+    fn top(&self, out: &mut String, width: usize) {
+        let Some(chars) = &self.chars else {
+            if let Some(title) = &self.title {
+                let title = truncate_to_width(title, width);
+                let _ = writeln!(out, "{}", self.theme.accent.paint(&title, self.color));
+            }
+            return;
+        };
+        let edge_width = width.saturating_sub(2);
+        let title = self
+            .title
+            .as_deref()
+            .map(|title| truncate_to_width(title, edge_width.saturating_sub(4)))
+            .filter(|title| !title.is_empty());
+        let line = match title {
+            Some(title) => {
+                let fill = edge_width.saturating_sub(visible_width(&title) + 3);
+                format!(
+                    "{}{}{}",
+                    self.theme.border.paint(
+                        &format!("{}{} ", chars.top_left, chars.horizontal),
+                        self.color
+                    ),
+                    self.theme.accent.paint(&title, self.color),
+                    self.theme.border.paint(
+                        &format!(" {}{}", horizontal(chars, fill), chars.top_right),
+                        self.color
+                    )
+                )
+            }
+            None => self.theme.border.paint(
+                &format!(
+                    "{}{}{}",
+                    chars.top_left,
+                    horizontal(chars, edge_width),
+                    chars.top_right
+                ),
+                self.color,
+            ),
+        };
+        let _ = writeln!(out, "{}", line);
+    }
+
+    /// Writes one line of content padded to `inner_width` between the side
+    /// borders.
+    fn row(&self, out: &mut String, content: &str, inner_width: usize) {
+        if self.chars.is_none() {
+            let _ = writeln!(out, "{}", content.trim_end());
+            return;
+        }
+        let padding = inner_width.saturating_sub(visible_width(content));
+        let _ = writeln!(
+            out,
+            "{} {}{} {}",
+            self.vertical,
+            content,
+            " ".repeat(padding),
+            self.vertical
+        );
+    }
+
+    fn bottom(&self, out: &mut String, width: usize) {
+        if let Some(chars) = &self.chars {
+            let border = format!(
+                "{}{}{}",
+                chars.bottom_left,
+                horizontal(chars, width.saturating_sub(2)),
+                chars.bottom_right
+            );
+            let _ = writeln!(out, "{}", self.theme.border.paint(&border, self.color));
+        }
+    }
+}
+
+fn horizontal(chars: &BorderChars, width: usize) -> String {
+    chars.horizontal.to_string().repeat(width)
+}
+
+/// Replaces `{key}` in a border title with the value of that field.
+fn expand_title(title: &str, report: &Report, options: &RenderOptions) -> String {
+    let mut expanded = String::new();
+    let mut rest = title;
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let field = after.find('}').and_then(|end| {
+            let key = &after[..end];
+            let line = report.lines.iter().find(|line| line.key == key)?;
+            Some((end, line.display_value(options.field(key).unit)))
+        });
+        match field {
+            Some((end, value)) => {
+                expanded.push_str(&value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                expanded.push('{');
+                rest = after;
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Columns each row needs however narrow the box: the side borders, the
+/// widest label and bar, and one column of value, or the palette swatches
+/// when they are wider.
+fn min_width(info: &[InfoLine], frame: &Frame, options: &RenderOptions) -> usize {
+    let bar_width = info
+        .iter()
+        .filter(|line| options.field(&line.key).bar != BarMode::Off)
+        .filter(|line| line.value.percentage().is_some())
+        .map(|_| options.bars.width + 3) // "[", "]" and the space after
+        .max()
+        .unwrap_or(0);
+    let row_width = label_width(info, options) + 1 + bar_width + 1;
+    let palette_width = palette_lines(options)
+        .iter()
+        .map(|line| visible_width(line))
+        .max()
+        .unwrap_or(0);
+    2 * frame.side_width() + row_width.max(palette_width)
+}
+
+/// Widest label with its separator.
+fn label_width(info: &[InfoLine], options: &RenderOptions) -> usize {
+    let separator_width = visible_width(&charset_text(&options.layout.separator, options));
+    info.iter()
+        .map(|line| visible_width(&charset_text(&line.label, options)) + separator_width)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Turns collected lines into `(label, value)` rows with painted values.
+/// Values too wide for [`value_width`] are wrapped into continuation rows
+/// with an empty label, or truncated. Bars go before the first row of their
+/// field.
+fn info_rows(info: &[InfoLine], frame: &Frame, options: &RenderOptions) -> Vec<(String, String)> {
+    let layout = &options.layout;
+    let width = value_width(label_width(info, options), frame, options);
+    let ellipsis = charset_text(&layout.ellipsis, options);
+
+    let mut rows = Vec::new();
+    for line in info {
+        let field = options.field(&line.key);
+        let percentage = line
+            .value
+            .percentage()
+            .filter(|_| field.bar != BarMode::Off);
+        let label = charset_text(&line.label, options);
+        let value = match (field.bar, percentage) {
+            (BarMode::Instead, Some(percentage)) => InfoLine {
+                value: Value::Percentage(percentage),
+                ..line.clone()
+            }
+            .display_value(None),
+            _ => line.display_value(field.unit),
+        };
+        let value = charset_text(&value, options);
+        let bar = percentage.map(|percentage| {
+            let inverted = INVERTED_BARS.contains(&line.key.as_str());
+            bar(percentage, inverted, options)
+        });
+        // Continuation rows line up after the bar
+        let indent = bar.as_ref().map_or(0, |bar| visible_width(bar) + 1);
+
+        let parts = match layout.overflow {
+            Overflow::Wrap => wrap_text(&value, width.saturating_sub(indent)),
+            Overflow::Truncate => vec![truncate_with_ellipsis(
+                &value,
+                width.saturating_sub(indent),
+                &ellipsis,
+            )],
+        };
+        for (i, part) in parts.iter().enumerate() {
+            let part = options.theme.value.paint(part, options.color);
+            let row = match (i, &bar) {
+                (0, Some(bar)) => (label.clone(), format!("{} {}", bar, part)),
+                (0, None) => (label.clone(), part),
+                _ => (String::new(), format!("{}{}", " ".repeat(indent), part)),
+            };
+            rows.push(row);
+        }
+    }
+    rows
+}
+
+/// `text` as it can be shown with `options.charset`.
+fn charset_text(text: &str, options: &RenderOptions) -> String {
+    match options.charset {
+        Charset::Unicode => text.to_string(),
+        Charset::Ascii => to_ascii(text),
+    }
+}
+
+/// Columns left for values after labels `label_width` wide: beside the art
+/// when that leaves at least `MIN_VALUE_WIDTH`, the whole box otherwise.
+fn value_width(label_width: usize, frame: &Frame, options: &RenderOptions) -> usize {
+    let full = frame
+        .inner_width(options.width)
+        .saturating_sub(label_width + 1);
+    if !matches!(
+        options.layout.position,
+        LogoPosition::Left | LogoPosition::Right
+    ) {
+        return full;
+    }
+    let art_width = options
+        .image
+        .as_ref()
+        .map_or_else(|| options.logo.width(), |image| image.columns);
+    let gap = options.layout.gap.unwrap_or(ART_GAP);
+    let beside = full.saturating_sub(art_width + gap);
+    if beside >= MIN_VALUE_WIDTH {
+        beside
+    } else {
+        full
+    }
+}
+
+/// `[████░░░░]`, colored by how close `percentage` is to full, or to empty
+/// when `inverted`.
+fn bar(percentage: f64, inverted: bool, options: &RenderOptions) -> String {
+    let bars = &options.bars;
+    let percentage = percentage.clamp(0.0, 100.0);
+    let filled = (percentage / 100.0 * bars.width as f64).round() as usize;
+    let level = if inverted {
+        100.0 - percentage
+    } else {
+        percentage
+    };
+    let fill_color = if level >= bars.critical {
+        bars.critical_color
+    } else if level >= bars.warning {
+        bars.warning_color
+    } else {
+        bars.color
+    };
+    format!(
+        "[{}{}]",
+        Style::fg(fill_color).paint(
+            &charset_text(&bars.filled, options).repeat(filled),
+            options.color
+        ),
+        charset_text(&bars.empty, options).repeat(bars.width - filled)
+    )
+}
+
+/// Writes the art next to the information inside the box, on the left when
+/// `art_left` is set. Returns the line and 1-based column where the art
+/// starts.
+fn display_side_by_side(
+    out: &mut String,
+    frame: &Frame,
+    art: &Block,
+    info: &Block,
+    art_left: bool,
+    layout: &Layout,
+    term_width: usize,
+) -> (usize, usize) {
+    let inner_width = frame.inner_width(term_width);
+    let (left, right) = if art_left { (art, info) } else { (info, art) };
+    // Without a set gap, art on the right is pushed against the border
+    let gap = layout.gap.unwrap_or(if art_left {
+        ART_GAP
+    } else {
+        inner_width.saturating_sub(left.width + right.width)
+    });
+
+    let max_lines = left.lines.len().max(right.lines.len());
+    let offset = |block: &Block| match layout.align {
+        Align::Top => 0,
+        Align::Center => (max_lines - block.lines.len()) / 2,
+    };
+    let (left_offset, right_offset) = (offset(left), offset(right));
+
+    frame.top(out, term_width);
+
+    for i in 0..max_lines {
+        let line = |block: &Block, offset: usize| {
+            let text = i
+                .checked_sub(offset)
+                .and_then(|i| block.lines.get(i))
+                .map_or("", String::as_str);
+            let padding = block.width.saturating_sub(visible_width(text));
+            format!("{}{}", text, " ".repeat(padding))
+        };
+
+        let line_content = format!(
+            "{}{}{}",
+            line(left, left_offset),
+            " ".repeat(gap),
+            line(right, right_offset)
+        );
+        frame.row(out, &line_content, inner_width);
+    }
+
+    frame.bottom(out, term_width);
+
+    // Below the top border, after "│ "
+    let (row, column) = (frame.top_height(), 1 + frame.side_width());
+    if art_left {
+        (row + left_offset, column)
+    } else {
+        (row + right_offset, column + left.width + gap)
+    }
+}
+
+/// Writes the art centered above the information, or below it unless
+/// `art_first` is set. Returns the line and 1-based column where the art
+/// starts.
+fn display_stacked(
+    out: &mut String,
+    frame: &Frame,
+    art: &Block,
+    info: &Block,
+    art_first: bool,
+    layout: &Layout,
+    term_width: usize,
+) -> (usize, usize) {
+    let inner_width = frame.inner_width(term_width);
+    let art_padding = inner_width.saturating_sub(art.width) / 2;
+
+    frame.top(out, term_width);
+
+    let write_line = |out: &mut String, padding: usize, line: &str| {
+        frame.row(
+            out,
+            &format!("{}{}", " ".repeat(padding), line),
+            inner_width,
+        );
+    };
+
+    let mut art_line = 0;
+    let blocks = if art_first { [art, info] } else { [info, art] };
+    for (i, block) in blocks.into_iter().enumerate() {
+        if i == 1 && !art.lines.is_empty() && !info.lines.is_empty() {
+            for _ in 0..stacked_gap(layout) {
+                write_line(out, 0, "");
+            }
+        }
+        let is_art = (i == 0) == art_first;
+        let padding = if is_art {
+            art_line = out.lines().count();
+            art_padding
+        } else {
+            0
+        };
+        for line in &block.lines {
+            write_line(out, padding, line);
+        }
+    }
+
+    frame.bottom(out, term_width);
+
+    (art_line, 1 + frame.side_width() + art_padding)
+}
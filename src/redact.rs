@@ -0,0 +1,490 @@
+use crate::info::Value;
+use crate::report::Report;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// Masks personal data in a report before it is shown, e.g. for screenshots.
+/// Each category is masked when `enabled` is set and its flag is on.
+///
+/// Hostname, username, SSID and serial are replaced wherever the collected
+/// value appears; addresses are found by pattern and numbered in order of
+/// appearance, so the same address always gets the same placeholder.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct Redact {
+    pub enabled: bool,
+    /// The machine's name as `<hostname>`.
+    pub hostname: bool,
+    /// The name of the user running rustin as `<user>`.
+    pub username: bool,
+    /// The Wi-Fi network name as `<ssid>`.
+    pub ssid: bool,
+    /// IPv4 and IPv6 addresses as `<ip-1>`, `<ip-2>`, ...
+    pub ip: bool,
+    /// MAC addresses as `<mac-1>`, `<mac-2>`, ...
+    pub mac: bool,
+    /// The battery's device path and model as `<serial>`.
+    pub serial: bool,
+    /// `$HOME`, `/home/NAME` and `/Users/NAME` as `~`.
+    pub home: bool,
+}
+
+impl Default for Redact {
+    fn default() -> Self {
+        Redact {
+            enabled: false,
+            hostname: true,
+            username: true,
+            ssid: true,
+            ip: true,
+            mac: true,
+            serial: true,
+            home: true,
+        }
+    }
+}
+
+impl Redact {
+    /// Masks the selected categories in the values, notes and ids of
+    /// `report`. Version numbers are left alone, as they never hold personal
+    /// data and four-part ones would look like IPv4 addresses.
+    pub fn apply(&self, report: &mut Report) {
+        self.apply_as(&Identity::current(), report);
+    }
+
+    fn apply_as(&self, identity: &Identity, report: &mut Report) {
+        if !self.enabled {
+            return;
+        }
+        let mut masker = Masker::new(self, identity, report);
+        for line in &mut report.lines {
+            match &mut line.value {
+                Value::Text(text) => *text = masker.mask(text),
+                Value::Version { name, .. } => *name = masker.mask(name),
+                Value::Counts(counts) => {
+                    for (source, _) in counts {
+                        *source = masker.mask(source);
+                    }
+                }
+                Value::Percentage(_)
+                | Value::Usage { .. }
+                | Value::Duration(_)
+                | Value::TimedOut(_) => {}
+            }
+            for text in [&mut line.note, &mut line.id].into_iter().flatten() {
+                *text = masker.mask(text);
+            }
+        }
+    }
+}
+
+/// Who and where rustin runs, read from the system rather than the report so
+/// it is masked even when `system.host` or `system.user` is not collected.
+struct Identity {
+    hostname: Option<String>,
+    username: Option<String>,
+    home: Option<String>,
+}
+
+impl Identity {
+    fn current() -> Self {
+        Identity {
+            hostname: whoami::fallible::hostname().ok(),
+            username: Some(whoami::username()),
+            home: env::var("HOME").ok(),
+        }
+    }
+}
+
+struct Masker<'a> {
+    redact: &'a Redact,
+    /// Values to replace as whole words, longest first.
+    words: Vec<(String, &'static str)>,
+    home: Option<String>,
+    home_pattern: Regex,
+    mac_pattern: Regex,
+    ipv4_pattern: Regex,
+    ipv6_pattern: Regex,
+    /// Placeholders handed out so far, per address.
+    numbered: HashMap<String, String>,
+    counts: HashMap<&'static str, usize>,
+}
+
+impl<'a> Masker<'a> {
+    fn new(redact: &'a Redact, identity: &Identity, report: &Report) -> Self {
+        let mut words = Vec::new();
+        if redact.hostname {
+            words.extend(identity.hostname.clone().map(|host| (host, "<hostname>")));
+        }
+        if redact.username {
+            words.extend(identity.username.clone().map(|user| (user, "<user>")));
+        }
+        for line in &report.lines {
+            let text = match &line.value {
+                Value::Text(text) => text.as_str(),
+                _ => continue,
+            };
+            match line.key.as_str() {
+                // Also taken from the report, should it differ from the system
+                "system.host" if redact.hostname => words.push((text.to_string(), "<hostname>")),
+                "system.user" if redact.username => words.push((text.to_string(), "<user>")),
+                // The value is the SSID when connected over Wi-Fi, with the
+                // interface as the note; otherwise it is the interface
+                "network.connection" if redact.ssid && line.note.is_some() => {
+                    words.push((text.to_string(), "<ssid>"))
+                }
+                _ => {}
+            }
+        }
+        if redact.serial
+            && let Some(device) = report
+                .lines
+                .iter()
+                .find(|line| line.key == "hardware.battery")
+                .and_then(|line| line.note.as_deref())
+        {
+            // "<native-path> <model>"
+            words.push((device.to_string(), "<serial>"));
+        }
+        words.retain(|(word, _)| !word.is_empty() && word != "Unknown");
+        words.sort_by_key(|(word, _)| std::cmp::Reverse(word.len()));
+
+        let home = identity
+            .home
+            .as_deref()
+            .map(|home| home.trim_end_matches('/').to_string())
+            .filter(|home| !home.is_empty());
+
+        Masker {
+            redact,
+            words,
+            home,
+            home_pattern: Regex::new(r"/(?:home|Users)/[^/\s]+").unwrap(),
+            mac_pattern: Regex::new(r"[0-9A-Fa-f]{2}(?:[:-][0-9A-Fa-f]{2}){5}").unwrap(),
+            ipv4_pattern: Regex::new(r"\d{1,3}(?:\.\d{1,3}){3}").unwrap(),
+            ipv6_pattern: Regex::new(r"[0-9A-Fa-f]*:[0-9A-Fa-f:]*").unwrap(),
+            numbered: HashMap::new(),
+            counts: HashMap::new(),
+        }
+    }
+
+    fn mask(&mut self, text: &str) -> String {
+        let mut text = text.to_string();
+        // Home paths go first, as they usually contain the username
+        if self.redact.home {
+            if let Some(home) = &self.home {
+                text = replace_path(&text, home, "~");
+            }
+            text = self.home_pattern.replace_all(&text, "~").into_owned();
+        }
+        for (word, placeholder) in &self.words {
+            text = replace_word(&text, word, placeholder);
+        }
+        // MAC addresses before IPv6, whose pattern they would also match
+        if self.redact.mac {
+            text = self.number(&text, Pattern::Mac);
+        }
+        if self.redact.ip {
+            text = self.number(&text, Pattern::Ipv4);
+            text = self.number(&text, Pattern::Ipv6);
+        }
+        text
+    }
+
+    /// Replaces each address matching `pattern` with a numbered placeholder.
+    /// Matches must stand alone: `std::fs::read` and version strings such as
+    /// `1.2.3.4.5` are not addresses.
+    fn number(&mut self, text: &str, pattern: Pattern) -> String {
+        let regex = match pattern {
+            Pattern::Mac => &self.mac_pattern,
+            Pattern::Ipv4 => &self.ipv4_pattern,
+            Pattern::Ipv6 => &self.ipv6_pattern,
+        };
+        let mut out = String::new();
+        let mut last = 0;
+        for found in regex.find_iter(text) {
+            let address = found.as_str();
+            let valid = stands_alone(text, found.start(), found.end(), pattern.separators())
+                && match pattern {
+                    Pattern::Mac => true,
+                    Pattern::Ipv4 => address.parse::<Ipv4Addr>().is_ok(),
+                    // Without a digit it is more likely a word like
+                    // `cafe::beef` than an address
+                    Pattern::Ipv6 => {
+                        address.matches(':').count() >= 2
+                            && address.contains(|c: char| c.is_ascii_digit())
+                            && address.parse::<Ipv6Addr>().is_ok()
+                    }
+                };
+            if !valid {
+                continue;
+            }
+            let category = match pattern {
+                Pattern::Mac => "mac",
+                Pattern::Ipv4 | Pattern::Ipv6 => "ip",
+            };
+            let key = address.to_lowercase();
+            let placeholder = match self.numbered.get(&key) {
+                Some(placeholder) => placeholder.clone(),
+                None => {
+                    let count = self.counts.entry(category).or_default();
+                    *count += 1;
+                    let placeholder = format!("<{}-{}>", category, count);
+                    self.numbered.insert(key, placeholder.clone());
+                    placeholder
+                }
+            };
+            out.push_str(&text[last..found.start()]);
+            out.push_str(&placeholder);
+            last = found.end();
+        }
+        out.push_str(&text[last..]);
+        out
+    }
+}
+
+/// Whether `text[start..end]` is a token of its own rather than part of a
+/// longer word or a longer run of `separators`. A `.` may follow when it
+/// ends a sentence.
+fn stands_alone(text: &str, start: usize, end: usize, separators: &[char]) -> bool {
+    let joins = |c: char| c.is_alphanumeric() || c == '_' || separators.contains(&c);
+    let before = text[..start].chars().next_back();
+    let mut after = text[end..].chars();
+    let joined_after = match after.next() {
+        Some('.') => after.next().is_some_and(char::is_alphanumeric),
+        Some(c) => joins(c),
+        None => false,
+    };
+    !before.is_some_and(joins) && !joined_after
+}
+
+#[derive(Clone, Copy)]
+enum Pattern {
+    Mac,
+    Ipv4,
+    Ipv6,
+}
+
+impl Pattern {
+    /// Characters between the parts of an address.
+    fn separators(self) -> &'static [char] {
+        match self {
+            Pattern::Mac => &[':', '-'],
+            Pattern::Ipv4 => &['.'],
+            // With an IPv4 address at the end
+            Pattern::Ipv6 => &[':', '.'],
+        }
+    }
+}
+
+/// Replaces `word` where it is not part of a longer word, so a username of
+/// `al` leaves `Alacritty` and `also` alone.
+fn replace_word(text: &str, word: &str, placeholder: &str) -> String {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let mut out = String::new();
+    let mut last = 0;
+    for (start, _) in text.match_indices(word) {
+        let end = start + word.len();
+        if start < last {
+            continue;
+        }
+        let before = text[..start].chars().next_back();
+        let after = text[end..].chars().next();
+        if before.is_some_and(is_word_char) || after.is_some_and(is_word_char) {
+            continue;
+        }
+        out.push_str(&text[last..start]);
+        out.push_str(placeholder);
+        last = end;
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+/// Replaces `path` and anything below it, but not `/home/alice2` for
+/// `/home/alice`.
+fn replace_path(text: &str, path: &str, placeholder: &str) -> String {
+    let mut out = String::new();
+    let mut last = 0;
+    for (start, _) in text.match_indices(path) {
+        let end = start + path.len();
+        if start < last
+            || text[end..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        {
+            continue;
+        }
+        out.push_str(&text[last..start]);
+        out.push_str(placeholder);
+        last = end;
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::info::InfoLine;
+
+    fn identity() -> Identity {
+        Identity {
+            hostname: Some("alpc".to_string()),
+            username: Some("al".to_string()),
+            home: Some("/home/al".to_string()),
+        }
+    }
+
+    fn redact(lines: Vec<InfoLine>) -> Vec<InfoLine> {
+        redact_with(Redact::default(), lines)
+    }
+
+    fn redact_with(redact: Redact, lines: Vec<InfoLine>) -> Vec<InfoLine> {
+        let mut report = Report { lines };
+        let redact = Redact {
+            enabled: true,
+            ..redact
+        };
+        redact.apply_as(&identity(), &mut report);
+        report.lines
+    }
+
+    fn text(value: &str) -> InfoLine {
+        InfoLine::new("terminal.font", "Font", Value::text(value))
+    }
+
+    fn value(line: &InfoLine) -> String {
+        line.value.to_string()
+    }
+
+    #[test]
+    fn masks_hostname_and_user_as_whole_words() {
+        let lines = redact(vec![text("al on alpc: Alacritty, also al_x and alpc2")]);
+        assert_eq!(
+            value(&lines[0]),
+            "<user> on <hostname>: Alacritty, also al_x and alpc2"
+        );
+    }
+
+    #[test]
+    fn masks_hostname_without_the_system_module() {
+        let lines = redact(vec![text("alpc")]);
+        assert_eq!(value(&lines[0]), "<hostname>");
+    }
+
+    #[test]
+    fn masks_fields_taken_from_the_report() {
+        let lines = redact(vec![
+            InfoLine::new("system.host", "Host", Value::text("other-host")),
+            InfoLine::new("network.connection", "Network", Value::text("Home Net"))
+                .with_note("wlan0"),
+            InfoLine::new("hardware.battery", "Battery", Value::Percentage(80.0))
+                .with_note("/sys/BAT0 DELL 1234")
+                .with_charging(true),
+        ]);
+        assert_eq!(value(&lines[0]), "<hostname>");
+        assert_eq!(value(&lines[1]), "<ssid>");
+        assert_eq!(lines[1].note.as_deref(), Some("wlan0"));
+        assert_eq!(lines[2].note.as_deref(), Some("<serial>"));
+        assert_eq!(lines[2].charging, Some(true));
+    }
+
+    #[test]
+    fn interface_names_are_not_ssids() {
+        let lines = redact(vec![InfoLine::new(
+            "network.connection",
+            "Network",
+            Value::text("eth0"),
+        )]);
+        assert_eq!(value(&lines[0]), "eth0");
+    }
+
+    #[test]
+    fn masks_home_directories() {
+        let lines = redact(vec![
+            text("/home/al/.config/kitty/kitty.conf"),
+            text("/home/al, /home/bob/fonts and /Users/carol"),
+            text("/home/alice/x"),
+        ]);
+        assert_eq!(value(&lines[0]), "~/.config/kitty/kitty.conf");
+        assert_eq!(value(&lines[1]), "~, ~/fonts and ~");
+        assert_eq!(value(&lines[2]), "~/x");
+    }
+
+    #[test]
+    fn masks_mount_points() {
+        let lines = redact(vec![
+            InfoLine::new("hardware.disk", "Disk", Value::Usage { used: 1, total: 2 })
+                .with_id("/home/al/usb"),
+        ]);
+        assert_eq!(lines[0].id.as_deref(), Some("~/usb"));
+    }
+
+    #[test]
+    fn numbers_addresses_in_order_of_appearance() {
+        let lines = redact(vec![
+            text("192.168.1.20 and 10.0.0.1, again 192.168.1.20."),
+            text("fe80::1ca:2 FE80::1CA:2 ::ffff:10.0.0.1 addr:10.0.0.1"),
+            text("aa:bb:cc:dd:ee:ff AA-BB-CC-DD-EE-FF 11:22:33:44:55:66"),
+        ]);
+        assert_eq!(value(&lines[0]), "<ip-1> and <ip-2>, again <ip-1>.");
+        assert_eq!(value(&lines[1]), "<ip-3> <ip-3> ::ffff:<ip-2> addr:<ip-2>");
+        assert_eq!(value(&lines[2]), "<mac-1> <mac-2> <mac-3>");
+    }
+
+    #[test]
+    fn leaves_text_that_only_looks_like_addresses() {
+        let untouched = [
+            "std::fs::read",
+            "cafe::beef",
+            "12:30:45",
+            "1.2.3.4.5",
+            "Linux 6.18.44-fc-v139",
+            "v10.0.0.1",
+            "Char:Yes",
+        ];
+        let lines = redact(untouched.iter().map(|value| text(value)).collect());
+        for (line, expected) in lines.iter().zip(untouched) {
+            assert_eq!(value(line), expected);
+        }
+    }
+
+    #[test]
+    fn leaves_version_numbers() {
+        let lines = redact(vec![InfoLine::new(
+            "system.kernel",
+            "Kernel",
+            Value::Version {
+                name: "Linux".to_string(),
+                version: Some("10.0.0.1".to_string()),
+            },
+        )]);
+        assert_eq!(value(&lines[0]), "Linux 10.0.0.1");
+    }
+
+    #[test]
+    fn only_masks_the_selected_categories() {
+        let redact = Redact {
+            ip: false,
+            username: false,
+            ..Redact::default()
+        };
+        let lines = redact_with(redact, vec![text("al@alpc 10.0.0.1")]);
+        assert_eq!(value(&lines[0]), "al@<hostname> 10.0.0.1");
+    }
+
+    #[test]
+    fn does_nothing_unless_enabled() {
+        let mut report = Report {
+            lines: vec![text("al@alpc 10.0.0.1")],
+        };
+        Redact::default().apply_as(&identity(), &mut report);
+        assert_eq!(value(&report.lines[0]), "al@alpc 10.0.0.1");
+    }
+}
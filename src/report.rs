@@ -0,0 +1,26 @@
+use crate::info::InfoLine;
+
+/// The result of one collection: every enabled field, in display order.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct Report {
+    pub lines: Vec<InfoLine>,
+}
+
+impl Report {
+    pub fn new(lines: Vec<InfoLine>) -> Self {
+        Report { lines }
+    }
+
+    /// Lines grouped by module, in order of first appearance.
+    pub fn modules(&self) -> Vec<(&str, Vec<&InfoLine>)> {
+        let mut modules: Vec<(&str, Vec<&InfoLine>)> = Vec::new();
+        for line in &self.lines {
+            match modules.iter_mut().find(|(name, _)| *name == line.module()) {
+                Some((_, lines)) => lines.push(line),
+                None => modules.push((line.module(), vec![line])),
+            }
+        }
+        modules
+    }
+}
@@ -0,0 +1,318 @@
+use rustin::{Config, Format, RenderOptions, Report};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a client may take to send its request, or to take the response.
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Largest request line and headers accepted, in bytes.
+const MAX_REQUEST_SIZE: u64 = 8 * 1024;
+
+/// Connections handled at once; more are answered with 503 right away.
+const MAX_CONNECTIONS: usize = 32;
+
+/// Serves the report over HTTP at `/json`, `/metrics` (Prometheus) and
+/// `/text`, collecting it again for a request once `refresh` has passed
+/// since the last collection.
+pub struct Server {
+    listener: TcpListener,
+    config: Config,
+    /// Used for `/text`; the format is set per endpoint.
+    options: RenderOptions,
+    refresh: Duration,
+    collect: fn(&Config) -> Report,
+    cache: Mutex<Cache>,
+    /// Signalled when a collection finishes.
+    collected: Condvar,
+    connections: AtomicUsize,
+}
+
+#[derive(Default)]
+struct Cache {
+    report: Option<(Instant, Report)>,
+    /// A request is collecting a new report.
+    collecting: bool,
+}
+
+impl Server {
+    pub fn bind(
+        addr: impl ToSocketAddrs,
+        config: Config,
+        options: RenderOptions,
+        refresh: Duration,
+    ) -> io::Result<Server> {
+        Ok(Server {
+            listener: TcpListener::bind(addr)?,
+            config,
+            options,
+            refresh,
+            collect: rustin::collect,
+            cache: Mutex::new(Cache::default()),
+            collected: Condvar::new(),
+            connections: AtomicUsize::new(0),
+        })
+    }
+
+    /// The address actually listened on, e.g. after binding port 0.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Answers requests, each on its own thread, at most
+    /// [`MAX_CONNECTIONS`] at a time. Never returns.
+    pub fn run(&self) {
+        thread::scope(|scope| {
+            for stream in self.listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                if self.connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                    self.connections.fetch_sub(1, Ordering::SeqCst);
+                    let _ = stream.set_write_timeout(Some(IO_TIMEOUT));
+                    let busy = Response::text(503, "Service Unavailable", "Too many requests\n");
+                    let _ = busy.write(&mut stream, false);
+                    continue;
+                }
+                scope.spawn(move || {
+                    // A panicking collector drops the connection but must
+                    // not take the slot or the server with it
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| self.handle(stream)));
+                    if let Ok(Err(err)) = result
+                        && self.config.verbose
+                    {
+                        eprintln!("rustin: serve: {}", err);
+                    }
+                    self.connections.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        })
+    }
+
+    fn handle(&self, mut stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+        let Some(request_line) = read_request(&stream)? else {
+            let response = Response::text(
+                431,
+                "Request Header Fields Too Large",
+                "Request too large\n",
+            );
+            response.write(&mut stream, false)?;
+            // Closing with the rest of the request unread would reset the
+            // connection before the client has read the response
+            stream.shutdown(Shutdown::Write)?;
+            io::copy(&mut (&stream).take(MAX_REQUEST_SIZE), &mut io::sink())?;
+            return Ok(());
+        };
+
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or_default();
+        let path = parts.next().unwrap_or_default();
+        let path = path.split('?').next().unwrap_or_default();
+
+        let response = if method != "GET" && method != "HEAD" {
+            Response::text(
+                405,
+                "Method Not Allowed",
+                "Only GET and HEAD are supported\n",
+            )
+        } else {
+            match path {
+                "/json" => Response::ok("application/json", self.render(Format::Json)),
+                "/metrics" => Response::ok(
+                    "text/plain; version=0.0.4; charset=utf-8",
+                    self.render(Format::Prometheus),
+                ),
+                "/text" => Response::ok("text/plain; charset=utf-8", self.render(Format::Text)),
+                "/" => Response::ok("text/plain; charset=utf-8", INDEX.to_string()),
+                _ => Response::text(404, "Not Found", "Not found\n"),
+            }
+        };
+        response.write(&mut stream, method == "HEAD")
+    }
+
+    fn render(&self, format: Format) -> String {
+        let report = self.report();
+        let mut options = self.options.clone();
+        options.format = format;
+        rustin::render(&report, &options)
+    }
+
+    /// The cached report, collected again when it is older than `refresh`.
+    /// Requests arriving during a collection get the previous report, or
+    /// wait for the first one, instead of starting their own.
+    fn report(&self) -> Report {
+        let mut cache = self.cache.lock().unwrap_or_else(|err| err.into_inner());
+        loop {
+            match &cache.report {
+                Some((collected, report))
+                    if collected.elapsed() < self.refresh || cache.collecting =>
+                {
+                    return report.clone();
+                }
+                None if cache.collecting => {
+                    cache = self
+                        .collected
+                        .wait(cache)
+                        .unwrap_or_else(|err| err.into_inner());
+                }
+                _ => break,
+            }
+        }
+        cache.collecting = true;
+        drop(cache);
+        let _collecting = Collecting { server: self };
+
+        // Collected without holding the lock, so requests are not held up
+        // by a slow probe
+        let report = (self.collect)(&self.config);
+
+        let mut cache = self.cache.lock().unwrap_or_else(|err| err.into_inner());
+        cache.report = Some((Instant::now(), report.clone()));
+        report
+    }
+}
+
+/// Ends the collection started by [`Server::report`] when dropped, also when
+/// the collector panics, so that waiting requests wake up and a later one
+/// collects again.
+struct Collecting<'a> {
+    server: &'a Server,
+}
+
+impl Drop for Collecting<'_> {
+    fn drop(&mut self) {
+        let mut cache = self
+            .server
+            .cache
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        cache.collecting = false;
+        self.server.collected.notify_all();
+    }
+}
+
+/// Reads the request and returns its first line, or `None` when the request
+/// line and headers are larger than [`MAX_REQUEST_SIZE`]. The headers are
+/// not needed, but are read so the client sees a complete exchange.
+fn read_request(stream: &TcpStream) -> io::Result<Option<String>> {
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST_SIZE));
+    let mut request_line = String::new();
+    let mut line = String::new();
+    loop {
+        line.clear();
+        // Stops at the end of a line, the end of the stream or the limit
+        let read = reader.read_line(&mut line)?;
+        if reader.get_ref().limit() == 0 && !line.ends_with('\n') {
+            return Ok(None);
+        }
+        if request_line.is_empty() {
+            request_line = line.clone();
+        } else if line.trim_end().is_empty() {
+            return Ok(Some(request_line));
+        }
+        if read == 0 {
+            return Ok(Some(request_line));
+        }
+    }
+}
+
+const INDEX: &str =
+    "rustin\n\n/json     JSON document\n/metrics  Prometheus metrics\n/text     Rendered text\n";
+
+struct Response {
+    status: u16,
+    reason: &'static str,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn ok(content_type: &'static str, body: String) -> Response {
+        Response {
+            status: 200,
+            reason: "OK",
+            content_type,
+            body,
+        }
+    }
+
+    fn text(status: u16, reason: &'static str, body: &str) -> Response {
+        Response {
+            status,
+            reason,
+            content_type: "text/plain; charset=utf-8",
+            body: body.to_string(),
+        }
+    }
+
+    fn write(&self, stream: &mut TcpStream, head_only: bool) -> io::Result<()> {
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.status,
+            self.reason,
+            self.content_type,
+            self.body.len()
+        )?;
+        if !head_only {
+            stream.write_all(self.body.as_bytes())?;
+        }
+        stream.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::mpsc;
+
+    static PANICKED: AtomicBool = AtomicBool::new(false);
+
+    /// Fails the first collection after a while, like a collector hitting a
+    /// bug, and succeeds afterwards.
+    fn panics_once(_: &Config) -> Report {
+        if !PANICKED.swap(true, Ordering::SeqCst) {
+            thread::sleep(Duration::from_millis(200));
+            panic!("collector failed");
+        }
+        Report::default()
+    }
+
+    #[test]
+    fn a_panicking_collection_does_not_block_other_requests() {
+        let mut server = Server::bind(
+            "127.0.0.1:0",
+            Config::default(),
+            RenderOptions::default(),
+            Duration::from_secs(60),
+        )
+        .unwrap();
+        server.collect = panics_once;
+        let server = Arc::new(server);
+
+        let first = {
+            let server = Arc::clone(&server);
+            thread::spawn(move || panic::catch_unwind(AssertUnwindSafe(|| server.report())))
+        };
+        // Arrives during the failing collection and waits for it
+        thread::sleep(Duration::from_millis(50));
+        let (sender, receiver) = mpsc::channel();
+        {
+            let server = Arc::clone(&server);
+            thread::spawn(move || sender.send(server.report()));
+        }
+
+        assert!(first.join().unwrap().is_err());
+        let second = receiver.recv_timeout(Duration::from_secs(5));
+        assert_eq!(second, Ok(Report::default()));
+        assert!(!server.cache.lock().unwrap().collecting);
+    }
+}
@@ -0,0 +1,222 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+use std::io::IsTerminal;
+use std::str::FromStr;
+
+/// A terminal color, written as an SGR escape code by [`Style::paint`].
+///
+/// Unlike `colored`, the codes never depend on the environment, so rendering
+/// the same report twice gives the same bytes. Serialized as the strings
+/// [`FromStr`] accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+#[non_exhaustive]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    /// One of the 256 indexed colors.
+    Ansi(u8),
+    Rgb(u8, u8, u8),
+}
+
+const NAMED_COLORS: &[(&str, Color)] = &[
+    ("black", Color::Black),
+    ("red", Color::Red),
+    ("green", Color::Green),
+    ("yellow", Color::Yellow),
+    ("blue", Color::Blue),
+    ("magenta", Color::Magenta),
+    ("cyan", Color::Cyan),
+    ("white", Color::White),
+    ("bright_black", Color::BrightBlack),
+    ("bright_red", Color::BrightRed),
+    ("bright_green", Color::BrightGreen),
+    ("bright_yellow", Color::BrightYellow),
+    ("bright_blue", Color::BrightBlue),
+    ("bright_magenta", Color::BrightMagenta),
+    ("bright_cyan", Color::BrightCyan),
+    ("bright_white", Color::BrightWhite),
+];
+
+impl Color {
+    /// The SGR parameters selecting this color as foreground or background.
+    fn code(&self, background: bool) -> String {
+        let (base, extended) = if background { (40, 48) } else { (30, 38) };
+        match *self {
+            Color::Ansi(index) => format!("{};5;{}", extended, index),
+            Color::Rgb(r, g, b) => format!("{};2;{};{};{}", extended, r, g, b),
+            named => {
+                let index = NAMED_COLORS
+                    .iter()
+                    .position(|(_, color)| *color == named)
+                    .unwrap_or_default();
+                // Bright colors start at 90 (100 for backgrounds)
+                let code = if index < 8 {
+                    base + index
+                } else {
+                    base + 60 + index - 8
+                };
+                code.to_string()
+            }
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Color::Ansi(index) => write!(f, "{}", index),
+            Color::Rgb(r, g, b) => write!(f, "#{:02x}{:02x}{:02x}", r, g, b),
+            named => {
+                let name = NAMED_COLORS
+                    .iter()
+                    .find(|(_, color)| *color == named)
+                    .map_or("", |(name, _)| name);
+                f.write_str(name)
+            }
+        }
+    }
+}
+
+impl From<Color> for String {
+    fn from(color: Color) -> String {
+        color.to_string()
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = ParseColorError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseColorError(String);
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid color `{}`, expected a name, 0-255 or #rrggbb",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+/// Accepts color names (`blue`, `bright_blue`), 256-color indexes (`208`)
+/// and hex colors (`#ff8800`).
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase().replace(['-', ' '], "_");
+        if let Some((_, color)) = NAMED_COLORS.iter().find(|(known, _)| *known == name) {
+            return Ok(*color);
+        }
+        if let Ok(index) = name.parse::<u8>() {
+            return Ok(Color::Ansi(index));
+        }
+        if let Some(hex) = name.strip_prefix('#')
+            && hex.len() == 6
+            && let Ok(rgb) = u32::from_str_radix(hex, 16)
+        {
+            return Ok(Color::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8));
+        }
+        Err(ParseColorError(s.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+}
+
+impl Style {
+    /// No color and no attributes.
+    pub const PLAIN: Style = Style {
+        fg: None,
+        bg: None,
+        bold: false,
+    };
+
+    pub const fn fg(color: Color) -> Style {
+        Style {
+            fg: Some(color),
+            bg: None,
+            bold: false,
+        }
+    }
+
+    pub const fn bold(self) -> Style {
+        Style { bold: true, ..self }
+    }
+
+    /// Wraps `text` in this style's escape codes, or returns it unchanged when
+    /// `enabled` is false or there is nothing to apply.
+    pub fn paint(&self, text: &str, enabled: bool) -> String {
+        let mut codes = Vec::new();
+        if self.bold {
+            codes.push("1".to_string());
+        }
+        if let Some(color) = self.fg {
+            codes.push(color.code(false));
+        }
+        if let Some(color) = self.bg {
+            codes.push(color.code(true));
+        }
+
+        if !enabled || codes.is_empty() || text.is_empty() {
+            text.to_string()
+        } else {
+            format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text)
+        }
+    }
+}
+
+/// When to emit color codes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ColorChoice {
+    /// Unless `NO_COLOR` is set, when stdout is a terminal or
+    /// `CLICOLOR_FORCE` is set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn enabled(self) -> bool {
+        let set = |name| env::var_os(name).is_some_and(|value| !value.is_empty());
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto if set("NO_COLOR") => false,
+            ColorChoice::Auto => {
+                env::var_os("CLICOLOR_FORCE").is_some_and(|value| value != "0")
+                    || std::io::stdout().is_terminal()
+            }
+        }
+    }
+}
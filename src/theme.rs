@@ -0,0 +1,89 @@
+use crate::style::{Color, Style};
+
+/// Styles for each part of the text format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Theme {
+    pub name: &'static str,
+    pub label: Style,
+    pub value: Style,
+    /// Text between each label and its value, see `Layout::separator`.
+    pub separator: Style,
+    pub border: Style,
+    /// Border title.
+    pub accent: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        THEMES[0]
+    }
+}
+
+const fn hex(rgb: u32) -> Style {
+    Style::fg(Color::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8))
+}
+
+/// Themes selectable by name; the first one is the default.
+pub const THEMES: &[Theme] = &[
+    Theme {
+        name: "default",
+        label: Style::fg(Color::Blue).bold(),
+        value: Style::PLAIN,
+        separator: Style::PLAIN,
+        border: Style::fg(Color::Blue).bold(),
+        accent: Style::fg(Color::Blue).bold(),
+    },
+    Theme {
+        name: "mono",
+        label: Style::PLAIN.bold(),
+        value: Style::PLAIN,
+        separator: Style::PLAIN,
+        border: Style::PLAIN,
+        accent: Style::PLAIN.bold(),
+    },
+    Theme {
+        name: "nord",
+        label: hex(0x88c0d0).bold(),
+        value: hex(0xd8dee9),
+        separator: hex(0x4c566a),
+        border: hex(0x4c566a),
+        accent: hex(0x81a1c1).bold(),
+    },
+    Theme {
+        name: "gruvbox",
+        label: hex(0xfabd2f).bold(),
+        value: hex(0xebdbb2),
+        separator: hex(0x928374),
+        border: hex(0x928374),
+        accent: hex(0xfe8019).bold(),
+    },
+    Theme {
+        name: "dracula",
+        label: hex(0xbd93f9).bold(),
+        value: hex(0xf8f8f2),
+        separator: hex(0x6272a4),
+        border: hex(0x6272a4),
+        accent: hex(0xff79c6).bold(),
+    },
+    Theme {
+        name: "catppuccin",
+        label: hex(0x89b4fa).bold(),
+        value: hex(0xcdd6f4),
+        separator: hex(0x6c7086),
+        border: hex(0x6c7086),
+        accent: hex(0xcba6f7).bold(),
+    },
+    Theme {
+        name: "solarized",
+        label: hex(0x268bd2).bold(),
+        value: hex(0x839496),
+        separator: hex(0x586e75),
+        border: hex(0x586e75),
+        accent: hex(0xb58900).bold(),
+    },
+];
+
+pub(crate) fn find_theme(name: &str) -> Option<Theme> {
+    THEMES.iter().find(|theme| theme.name == name).copied()
+}
@@ -1,23 +1,47 @@
+use crate::logo::Logo;
+use crate::output::Charset;
+use std::collections::HashMap;
+use std::env;
 use std::fs;
-use unicode_width::UnicodeWidthStr;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-pub fn break_long_text(text: &str, max_line_length: usize) -> Vec<String> {
+/// Breaks `text` at spaces into lines of at most `width` columns, splitting
+/// words longer than a line. Text that fits is returned as it is.
+pub fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    if visible_width(text) <= width {
+        return vec![text.to_string()];
+    }
     let mut lines = Vec::new();
     let mut current_line = String::new();
 
     for word in text.split_whitespace() {
-        if current_line.len() + word.len() + 1 > max_line_length && !current_line.is_empty() {
-            lines.push(current_line.trim().to_string());
-            current_line = String::new();
+        if !current_line.is_empty()
+            && visible_width(&current_line) + 1 + visible_width(word) > width
+        {
+            lines.push(std::mem::take(&mut current_line));
         }
         if !current_line.is_empty() {
             current_line.push(' ');
         }
         current_line.push_str(word);
+
+        while visible_width(&current_line) > width {
+            let head = truncate_to_width(&current_line, width);
+            // Keep at least one character per line, however narrow
+            let split = match head.len() {
+                0 => current_line.chars().next().map_or(0, char::len_utf8),
+                len => len,
+            };
+            let rest = current_line.split_off(split);
+            lines.push(std::mem::replace(&mut current_line, rest));
+        }
     }
 
-    if !current_line.is_empty() {
-        lines.push(current_line.trim().to_string());
+    if !current_line.is_empty() || lines.is_empty() {
+        lines.push(current_line);
     }
 
     lines
@@ -28,36 +52,120 @@ pub fn visible_width(s: &str) -> usize {
     UnicodeWidthStr::width(stripped.as_str())
 }
 
+/// Cuts `text` down to at most `width` columns.
+pub fn truncate_to_width(text: &str, width: usize) -> String {
+    let mut result = String::new();
+    let mut current_width = 0;
+    for ch in text.chars() {
+        let ch_width = ch.width().unwrap_or(0);
+        if current_width + ch_width > width {
+            break;
+        }
+        result.push(ch);
+        current_width += ch_width;
+    }
+    result
+}
+
+/// `text` cut down to at most `width` columns, ending in `ellipsis` when it
+/// had to be shortened.
+pub fn truncate_with_ellipsis(text: &str, width: usize, ellipsis: &str) -> String {
+    if visible_width(text) <= width {
+        return text.to_string();
+    }
+    let kept = truncate_to_width(text, width.saturating_sub(visible_width(ellipsis)));
+    truncate_to_width(&format!("{}{}", kept, ellipsis), width)
+}
+
+/// Stand-ins for the non-ASCII characters rustin draws itself.
+const ASCII_REPLACEMENTS: &[(&[char], &str)] = &[
+    (&['─', '━', '═'], "-"),
+    (&['│', '┃', '║'], "|"),
+    (
+        &[
+            '┌', '┐', '└', '┘', '╭', '╮', '╰', '╯', '╔', '╗', '╚', '╝', '┏', '┓', '┗', '┛', '├',
+            '┤', '┬', '┴', '┼',
+        ],
+        "+",
+    ),
+    (&['█', '▓', '▒', '■', '▀', '▄', '▌', '▐'], "#"),
+    (&['░'], "-"),
+    (&['●', '•'], "*"),
+    (&['°'], "o"),
+    (&['…'], "..."),
+];
+
+/// `text` with only ASCII characters: box drawing and block characters
+/// become lookalikes, anything else a `?` per column.
+pub(crate) fn to_ascii(text: &str) -> String {
+    let mut ascii = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if ch.is_ascii() {
+            ascii.push(ch);
+            continue;
+        }
+        match ASCII_REPLACEMENTS
+            .iter()
+            .find(|(chars, _)| chars.contains(&ch))
+        {
+            Some((_, replacement)) => ascii.push_str(replacement),
+            None => ascii.push_str(&"?".repeat(ch.width().unwrap_or(0))),
+        }
+    }
+    ascii
+}
+
+/// Unicode when the locale (the first of `LC_ALL`, `LC_CTYPE` and `LANG`
+/// that is set) uses UTF-8 and the terminal is neither `dumb` nor a serial
+/// `vt*` terminal. Without a locale the C locale applies, which is ASCII.
+pub fn detect_charset() -> Charset {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let utf8 = locale.contains("utf-8") || locale.contains("utf8");
+    let term = env::var("TERM").unwrap_or_default();
+    let limited_terminal = term == "dumb" || term.starts_with("vt");
+    if utf8 && !limited_terminal {
+        Charset::Unicode
+    } else {
+        Charset::Ascii
+    }
+}
+
 pub fn get_version(cmd: &str, args: &[&str]) -> Option<String> {
     use regex::Regex;
-    use std::process::Command;
-
-    Command::new(cmd)
-        .args(args)
-        .output()
-        .ok()
-        .and_then(|output| {
-            if output.status.success() {
-                let version_str = String::from_utf8_lossy(&output.stdout);
-                let re = Regex::new(r"(\d+\.\d+(\.\d+)?)").unwrap();
-                re.captures(&version_str)
-                    .map(|caps| caps[1].to_string())
-                    .or_else(|| {
-                        version_str
-                            .lines()
-                            .next()
-                            .and_then(|line| line.split_whitespace().nth(1).map(|s| s.to_string()))
-                    })
-            } else {
-                None
-            }
+
+    let version_str = crate::command::run(cmd, args).ok()?;
+    let re = Regex::new(r"(\d+\.\d+(\.\d+)?)").unwrap();
+    re.captures(&version_str)
+        .map(|caps| caps[1].to_string())
+        .or_else(|| {
+            version_str
+                .lines()
+                .next()
+                .and_then(|line| line.split_whitespace().nth(1).map(|s| s.to_string()))
         })
 }
 
-pub fn read_ascii_art(file_path: &str) -> Option<Vec<String>> {
-    fs::read_to_string(file_path)
-        .ok()
-        .map(|content| content.lines().map(|line| line.to_string()).collect())
+/// Reads the `KEY=value` pairs of `/etc/os-release`, falling back to
+/// `/usr/lib/os-release`. Quotes around values are removed.
+pub fn read_os_release() -> HashMap<String, String> {
+    let contents = ["/etc/os-release", "/usr/lib/os-release"]
+        .iter()
+        .find_map(|path| fs::read_to_string(path).ok())
+        .unwrap_or_default();
+
+    contents
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| {
+            let value = value.trim().trim_matches('"').trim_matches('\'');
+            (key.trim().to_string(), value.to_string())
+        })
+        .collect()
 }
 
 pub fn get_terminal_size() -> (usize, usize) {
@@ -68,70 +176,86 @@ pub fn get_terminal_size() -> (usize, usize) {
     }
 }
 
-pub fn format_ascii_art_for_display(ascii_art: &[String], max_height: usize) -> Vec<String> {
-    let art_height = ascii_art.len();
-
-    if art_height <= max_height {
-        ascii_art.to_vec()
-    } else {
-        ascii_art.iter().take(max_height).cloned().collect()
+/// Size of one terminal cell in pixels, assuming 8x16 when the terminal
+/// does not report its pixel size.
+pub(crate) fn get_cell_size() -> (u32, u32) {
+    #[cfg(unix)]
+    {
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        let ok = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0;
+        if ok && size.ws_col > 0 && size.ws_row > 0 && size.ws_xpixel > 0 && size.ws_ypixel > 0 {
+            return (
+                (size.ws_xpixel / size.ws_col) as u32,
+                (size.ws_ypixel / size.ws_row) as u32,
+            );
+        }
     }
+    (8, 16)
 }
 
-pub fn calculate_layout(
-    ascii_art: &[String],
-    info_lines: &[(String, String)],
-    terminal_width: usize,
-) -> (Vec<String>, Vec<String>) {
-    let art_width = ascii_art
-        .iter()
-        .map(|line| visible_width(line))
-        .max()
-        .unwrap_or(0);
+pub fn format_ascii_art_for_display(ascii_art: &Logo, max_height: usize) -> Logo {
+    ascii_art.truncate(max_height)
+}
 
-    let info_width = info_lines
-        .iter()
-        .map(|(label, value)| visible_width(label) + visible_width(value) + 2) // +2 for ": " spacing
-        .max()
-        .unwrap_or(0);
+/// Runs every task on its own thread and waits for each one at most its
+/// timeout, measured from the call. Tasks that miss their deadline yield
+/// `None` and are left to finish in the background.
+pub fn run_with_deadlines<T, F>(tasks: Vec<(Duration, F)>) -> Vec<Option<T>>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let start = Instant::now();
+    let (sender, receiver) = mpsc::channel();
+    let mut deadlines = Vec::with_capacity(tasks.len());
+    for (index, (timeout, task)) in tasks.into_iter().enumerate() {
+        deadlines.push(start + timeout);
+        let sender = sender.clone();
+        thread::spawn(move || {
+            let _ = sender.send((index, task()));
+        });
+    }
+    drop(sender);
 
-    let padding = 4; // Space between art and info
+    let mut results: Vec<Option<T>> = deadlines.iter().map(|_| None).collect();
+    let mut pending = vec![true; deadlines.len()];
 
-    let total_width_needed = art_width + padding + info_width;
+    loop {
+        // Results that arrived while waiting for another task count even if
+        // their own deadline has passed since
+        while let Ok((index, result)) = receiver.try_recv() {
+            if pending[index] {
+                results[index] = Some(result);
+                pending[index] = false;
+            }
+        }
 
-    if total_width_needed <= terminal_width {
-        (ascii_art.to_vec(), vec![])
-    } else {
-        let available_art_width = terminal_width.saturating_sub(info_width + padding);
-
-        let cropped_art: Vec<String> = if available_art_width > 0 {
-            ascii_art
-                .iter()
-                .map(|line| {
-                    if visible_width(line) > available_art_width {
-                        // Truncate line to fit available width
-                        let mut result = String::new();
-                        let mut current_width = 0;
-
-                        for ch in line.chars() {
-                            let ch_width = unicode_width::UnicodeWidthChar::width(ch).unwrap_or(0);
-                            if current_width + ch_width <= available_art_width {
-                                result.push(ch);
-                                current_width += ch_width;
-                            } else {
-                                break;
-                            }
-                        }
-                        result
-                    } else {
-                        line.clone()
-                    }
-                })
-                .collect()
-        } else {
-            vec![]
+        let now = Instant::now();
+        for (index, deadline) in deadlines.iter().enumerate() {
+            if *deadline <= now {
+                pending[index] = false;
+            }
+        }
+
+        let next_deadline = deadlines
+            .iter()
+            .zip(&pending)
+            .filter(|(_, pending)| **pending)
+            .map(|(deadline, _)| *deadline)
+            .min();
+        let Some(next_deadline) = next_deadline else {
+            break;
         };
 
-        (cropped_art, vec![])
+        match receiver.recv_timeout(next_deadline - now) {
+            Ok((index, result)) if pending[index] => {
+                results[index] = Some(result);
+                pending[index] = false;
+            }
+            Ok(_) | Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
     }
+
+    results
 }
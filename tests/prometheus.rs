@@ -0,0 +1,74 @@
+//! The Prometheus format keeps one series per fact, whatever the collectors
+//! returned.
+
+use rustin::{Format, InfoLine, Kind, RenderOptions, Report, Value, render};
+
+fn metrics(lines: Vec<InfoLine>) -> String {
+    let mut options = RenderOptions::default();
+    options.format = Format::Prometheus;
+    render(&Report::new(lines), &options)
+}
+
+#[test]
+fn timed_out_fields_are_flagged_per_module() {
+    let out = metrics(vec![
+        InfoLine::new("system.host", "Host", Value::text("vm")),
+        InfoLine::new("hardware.memory", "Memory", Value::TimedOut(Kind::Usage)),
+        InfoLine::new("hardware.cpu", "CPU", Value::TimedOut(Kind::Text)),
+    ]);
+    assert!(
+        out.contains("rustin_info{system_host=\"vm\"} 1\n"),
+        "{}",
+        out
+    );
+    assert!(
+        out.contains("rustin_collect_timeout{module=\"system\"} 0\n"),
+        "{}",
+        out
+    );
+    assert!(
+        out.contains("rustin_collect_timeout{module=\"hardware\"} 1\n"),
+        "{}",
+        out
+    );
+    assert!(!out.contains("Timed out"), "{}", out);
+    assert!(!out.contains("rustin_hardware_memory"), "{}", out);
+}
+
+#[test]
+fn placeholders_of_numeric_fields_are_left_out() {
+    let out = metrics(vec![
+        InfoLine::new(
+            "hardware.battery",
+            "Battery",
+            Value::text("No battery found"),
+        ),
+        InfoLine::new(
+            "software.packages",
+            "Packages",
+            Value::text("No packages found"),
+        ),
+    ]);
+    assert!(!out.contains("rustin_info"), "{}", out);
+    assert!(!out.contains("No "), "{}", out);
+}
+
+#[test]
+fn battery_charging_is_a_gauge() {
+    let out = metrics(vec![
+        InfoLine::new("hardware.battery", "Battery", Value::Percentage(80.0))
+            .with_note("/sys/BAT0 DELL 1234")
+            .with_charging(true),
+    ]);
+    assert!(
+        out.contains("rustin_hardware_battery_ratio 0.8\n"),
+        "{}",
+        out
+    );
+    assert!(
+        out.contains("rustin_hardware_battery_charging 1\n"),
+        "{}",
+        out
+    );
+    assert!(!out.contains("DELL"), "{}", out);
+}
@@ -0,0 +1,109 @@
+//! Requests against `rustin serve` bound to a free port on localhost.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::process::{Child, ChildStderr, Command, Stdio};
+
+/// A `rustin serve` process collecting only the quick system module, killed
+/// when dropped.
+struct Serve {
+    child: Child,
+    addr: SocketAddr,
+    /// Kept open so the server can still write to stderr.
+    _stderr: BufReader<ChildStderr>,
+}
+
+impl Serve {
+    fn start() -> Serve {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_rustin"))
+            .args(["--no-config", "-m", "system", "serve"])
+            .args(["--listen", "127.0.0.1:0", "--refresh", "60"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        // "rustin: listening on http://127.0.0.1:PORT"
+        let mut stderr = BufReader::new(child.stderr.take().unwrap());
+        let mut line = String::new();
+        stderr.read_line(&mut line).unwrap();
+        let addr = line
+            .trim()
+            .rsplit("http://")
+            .next()
+            .and_then(|addr| addr.parse().ok())
+            .unwrap_or_else(|| panic!("unexpected output: {:?}", line));
+        Serve {
+            child,
+            addr,
+            _stderr: stderr,
+        }
+    }
+
+    /// Sends `request` and returns the status line and body of the response.
+    fn send(&self, request: &str) -> (String, String) {
+        let mut stream = TcpStream::connect(self.addr).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.lines().next().unwrap().to_string();
+        (status, body.to_string())
+    }
+
+    fn get(&self, path: &str) -> (String, String) {
+        self.send(&format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path))
+    }
+}
+
+impl Drop for Serve {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn serves_json() {
+    let (status, body) = Serve::start().get("/json");
+    assert_eq!(status, "HTTP/1.1 200 OK");
+    assert!(body.contains("\"schema_version\""), "{}", body);
+    assert!(body.contains("\"system.uptime\""), "{}", body);
+}
+
+#[test]
+fn serves_metrics() {
+    let (status, body) = Serve::start().get("/metrics");
+    assert_eq!(status, "HTTP/1.1 200 OK");
+    assert!(
+        body.contains("# TYPE rustin_system_uptime_seconds gauge"),
+        "{}",
+        body
+    );
+}
+
+#[test]
+fn serves_text() {
+    let (status, body) = Serve::start().get("/text?ignored=1");
+    assert_eq!(status, "HTTP/1.1 200 OK");
+    assert!(body.contains("Uptime"), "{}", body);
+}
+
+#[test]
+fn unknown_path_is_not_found() {
+    let (status, _) = Serve::start().get("/nope");
+    assert_eq!(status, "HTTP/1.1 404 Not Found");
+}
+
+#[test]
+fn other_methods_are_not_allowed() {
+    let (status, _) = Serve::start().send("POST /json HTTP/1.1\r\nContent-Length: 0\r\n\r\n");
+    assert_eq!(status, "HTTP/1.1 405 Method Not Allowed");
+}
+
+#[test]
+fn oversized_requests_are_refused() {
+    let header = format!("X-Filler: {}\r\n", "a".repeat(100));
+    let request = format!("GET /json HTTP/1.1\r\n{}\r\n", header.repeat(100));
+    let (status, _) = Serve::start().send(&request);
+    assert_eq!(status, "HTTP/1.1 431 Request Header Fields Too Large");
+}
@@ -0,0 +1,162 @@
+//! Snapshots of the text format at fixed widths. Run with
+//! `UPDATE_SNAPSHOTS=1` to rewrite them after an intended change.
+
+use rustin::{InfoLine, Logo, RenderOptions, Report, Value, render};
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const LOGO: &str = r"  /\
+ /  \
+/ /\ \
+\ \/ /
+ \  /
+  \/
+";
+
+fn report() -> Report {
+    Report::new(vec![
+        InfoLine::new("system.host", "Host", Value::text("workstation")),
+        InfoLine::new("system.user", "User", Value::text("alice")),
+        InfoLine::new(
+            "system.os",
+            "OS",
+            Value::Version {
+                name: "Debian GNU/Linux".to_string(),
+                version: Some("12".to_string()),
+            },
+        ),
+        InfoLine::new("system.uptime", "Uptime", Value::Duration(93_780)),
+        InfoLine::new(
+            "software.packages",
+            "Packages",
+            Value::Counts(vec![
+                ("dpkg".to_string(), 1834),
+                ("flatpak".to_string(), 12),
+            ]),
+        ),
+        InfoLine::new(
+            "hardware.cpu",
+            "CPU",
+            Value::text("AMD Ryzen 7 PRO 7840U w/ Radeon 780M Graphics (16) @ 5.13 GHz"),
+        ),
+        InfoLine::new(
+            "hardware.memory",
+            "Memory",
+            Value::Usage {
+                used: 6 << 30,
+                total: 32 << 30,
+            },
+        ),
+        InfoLine::new(
+            "hardware.disk",
+            "Disk",
+            Value::Usage {
+                used: 210 << 30,
+                total: 512 << 30,
+            },
+        )
+        .with_note("ext4"),
+    ])
+}
+
+fn options_at(width: usize) -> RenderOptions {
+    let mut options = RenderOptions::default();
+    options.width = width;
+    options.height = 24;
+    options.logo = Logo::parse(LOGO);
+    options
+}
+
+fn render_at(width: usize) -> String {
+    render(&report(), &options_at(width))
+}
+
+/// The palette is only drawn with colors, so this keeps the escape codes.
+fn render_with_palette_at(width: usize) -> String {
+    let mut options = options_at(width);
+    options.color = true;
+    options.palette.enabled = true;
+    render(&report(), &options)
+}
+
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(name);
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+        actual == expected,
+        "{} differs from the snapshot\n--- expected\n{}--- actual\n{}",
+        name,
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn text_40_columns() {
+    assert_snapshot("text_40.txt", &render_at(40));
+}
+
+#[test]
+fn text_80_columns() {
+    assert_snapshot("text_80.txt", &render_at(80));
+}
+
+#[test]
+fn text_200_columns() {
+    assert_snapshot("text_200.txt", &render_at(200));
+}
+
+#[test]
+fn every_row_fills_the_width() {
+    for width in [40, 80, 200] {
+        for line in render_at(width).lines() {
+            assert_eq!(
+                unicode_width::UnicodeWidthStr::width(line),
+                width,
+                "{:?}",
+                line
+            );
+        }
+    }
+}
+
+#[test]
+fn narrow_widths_are_raised_to_the_minimum() {
+    let widths: Vec<usize> = render_at(0)
+        .lines()
+        .map(unicode_width::UnicodeWidthStr::width)
+        .collect();
+    // "│ " + "Packages" + " " + one column + " │"
+    assert!(widths.iter().all(|width| *width == 14), "{:?}", widths);
+}
+
+#[test]
+fn palette_at_narrow_width() {
+    assert_snapshot("text_palette_narrow.txt", &render_with_palette_at(0));
+}
+
+#[test]
+fn palette_rows_fit_inside_the_border() {
+    for width in [0, 10, 20, 27, 40] {
+        let text = render_with_palette_at(width);
+        let widths: Vec<usize> = strip_ansi_escapes::strip_str(&text)
+            .lines()
+            .map(unicode_width::UnicodeWidthStr::width)
+            .collect();
+        // Eight swatches of three blocks between "│ " and " │"
+        let expected = width.max(28);
+        assert!(
+            widths.iter().all(|row| *row == expected),
+            "{}: {:?}",
+            width,
+            widths
+        );
+    }
+}